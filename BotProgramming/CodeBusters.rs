@@ -1,19 +1,23 @@
 extern crate rand;
+use rand::rngs::StdRng;
 use rand::Rng;
-use std::cell::RefCell;
+use rand::SeedableRng;
 use std::cmp::PartialEq;
 use std::fmt::format;
 use std::io;
-use std::rc::Rc;
+use std::io::Write;
+use std::time::{Duration, Instant};
 
 const MAP_RIGHT_WALL: i32 = 16000;
 const MAP_BOTTOM_WALL: i32 = 9000;
 
 const OUTER_BUSTING_RADIUS: i32 = 1750;
 const INNER_BUSTING_RADIUS: i32 = 900;
+const STUN_RADIUS: i32 = 1760;
 
 const BASE_RELEASE_RADIUS: i32 = 1600;
 const LINE_OF_SIGHT_RADIUS: i32 = 2200;
+const RADAR_RADIUS: i32 = 4400;
 
 const CAPTURE_RADIUS: i32 = 6500;
 const ORIGIN_THROWING_OFFSET: i32 = 500;
@@ -21,15 +25,213 @@ const THROW_MARGIN: f64 = 0.9;
 const CAMPING_MARGIN: f64 = 0.1;
 
 const START_CAMPING_TURN: i32 = 100;
+const CAMPING_SCORE_SHIFT: i32 = 30;
+const CAMPING_FEW_GHOSTS_LEFT: i32 = 4;
+const CAMPING_REPLACE_TURNS: i32 = 20;
+const LANE_BINS: usize = 9;
+const LANE_SPREAD_BINS: usize = 1;
+const LANE_MIN_SIGHTINGS: i32 = 5;
 const CAMPING_RADIUS: i32 = 2500;
 const TURNS_UNTIL_GLOBAL_PICKS: i32 = 50;
+const HARD_GHOST_STAMINA: i32 = 15;
 const STUN_COOLDOWN: i32 = 20;
+const GHOST_FLEE_DISTANCE: i32 = 400;
+
+const BUSTER_MOVE_DISTANCE: i32 = 800;
+const HERDING_DISTANCE: i32 = 1000;
+const HERDING_DONE_DISTANCE: i32 = 3000;
+const HERDING_MIN_PROGRESS: i32 = 300;
+const HERDING_REACH: i32 = 1600;
+const BUSTING_RING_MARGIN: i32 = 100;
+const BUSTING_RING_SAMPLES: i32 = 24;
+const EXPLORATION_CELL_SIZE: i32 = 500;
+const EXPLORATION_SPREAD_RADIUS: i32 = 4400;
+const EXPLORATION_HINT_BONUS: i32 = 60;
+const PROBABLE_MATCH_RADIUS: i32 = 1600;
+
+const EJECT_THROW_DISTANCE: i32 = 1700;
+const EJECT_HANDOFF_CATCH_DISTANCE: i32 = 1300;
+const EJECT_HANDOFF_GAIN: i32 = 2500;
+const EJECT_THREAT_RADIUS: i32 = 2560;
+
+const RADAR_MIN_UNSEEN_CELLS: i32 = 100;
+const RADAR_MIN_BASE_DISTANCE: i32 = 5000;
+
+const MEMORY_REVISIT_RADIUS: i32 = 1800;
+const MEMORY_UNCERTAINTY_TURNS: i32 = 5;
+
+const ASSIGNMENT_GHOST_REWARD: i32 = 60;
+const ASSIGNMENT_STUN_REWARD: i32 = 50;
+const ASSIGNMENT_STUN_CARRIER_REWARD: i32 = 100;
+const ASSIGNMENT_CAMPER_PENALTY: i32 = 20;
+const ASSIGNMENT_CANDIDATES: usize = 4;
+const ASSIGNMENT_STUN_ARMED_REWARD: i32 = 20;
+const ASSIGNMENT_DANGER_PENALTY: i32 = 15;
+const ASSIGNMENT_COMMIT_BONUS: i32 = 10;
+const INTENT_STILL_DISTANCE: i32 = 200;
+const INTENT_CLOSING_DISTANCE: i32 = 400;
+const ESCORT_REWARD: i32 = 50;
+const ESCORT_THREAT_RADIUS: i32 = 4000;
+const ESCORT_MEMORY_TURNS: i32 = 20;
+const ESCORT_OFFSET: i32 = 800;
+const ROUTE_HEADINGS: i32 = 16;
+const ROUTE_MEMORY_TURNS: i32 = 5;
+const ROUTE_DANGER_WEIGHT: i32 = 4;
+const COOP_TARGET_BUST_TURNS: i32 = 8;
+const COOP_SHORTFALL_PENALTY: i32 = 10;
+const CONTEST_REINFORCE_TURNS: i32 = 4;
+const USE_SEARCH: bool = false;
+const SEARCH_DEPTH: i32 = 6;
+const SEARCH_BEAM_WIDTH: i32 = 4;
+const SEARCH_BUDGET_MS: i32 = 40;
+const SEARCH_OVERRIDE_MARGIN: i32 = 30;
+const SEARCH_GHOST_CANDIDATES: usize = 3;
+const SEARCH_SCORE_WEIGHT: i32 = 1000;
+const SEARCH_CARRY_WEIGHT: i32 = 400;
+const SEARCH_STUNNED_WEIGHT: i32 = 150;
+const SEARCH_STUN_READY_WEIGHT: i32 = 20;
+const SEARCH_DISTANCE_DIVISOR: i32 = 100;
 
 macro_rules! parse_input {
     ($x:expr, $t:ident) => {
         $x.trim().parse::<$t>().unwrap()
     };
 }
+
+macro_rules! strategy_params {
+    ($($name:ident: $t:ty = $default:expr,)*) => {
+        #[derive(Debug, Clone)]
+        struct StrategyParams {
+            $($name: $t,)*
+        }
+
+        impl StrategyParams {
+            fn new() -> StrategyParams {
+                StrategyParams {
+                    $($name: $default,)*
+                }
+            }
+            fn set(&mut self, key: &str, value: &str) -> bool {
+                match key {
+                    $(stringify!($name) => match value.parse::<$t>() {
+                        Ok(parsed) => {
+                            self.$name = parsed;
+                            true
+                        }
+                        Err(_) => false,
+                    },)*
+                    _ => false,
+                }
+            }
+            fn to_config(&self) -> String {
+                let mut lines = Vec::new();
+                $(lines.push(format!("{} = {} # {}", stringify!($name), self.$name, stringify!($t)));)*
+                lines.join("\n")
+            }
+        }
+    };
+}
+
+// everything worth tuning, the consts above are what the arena build plays with
+strategy_params! {
+    throw_margin: f64 = THROW_MARGIN,
+    camping_margin: f64 = CAMPING_MARGIN,
+    capture_radius: i32 = CAPTURE_RADIUS,
+    start_camping_turn: i32 = START_CAMPING_TURN,
+    camping_radius: i32 = CAMPING_RADIUS,
+    camping_score_shift: i32 = CAMPING_SCORE_SHIFT,
+    camping_few_ghosts_left: i32 = CAMPING_FEW_GHOSTS_LEFT,
+    camping_replace_turns: i32 = CAMPING_REPLACE_TURNS,
+    lane_min_sightings: i32 = LANE_MIN_SIGHTINGS,
+    turns_until_global_picks: i32 = TURNS_UNTIL_GLOBAL_PICKS,
+    hard_ghost_stamina: i32 = HARD_GHOST_STAMINA,
+    herding_distance: i32 = HERDING_DISTANCE,
+    herding_done_distance: i32 = HERDING_DONE_DISTANCE,
+    herding_min_progress: i32 = HERDING_MIN_PROGRESS,
+    herding_reach: i32 = HERDING_REACH,
+    busting_ring_margin: i32 = BUSTING_RING_MARGIN,
+    exploration_spread_radius: i32 = EXPLORATION_SPREAD_RADIUS,
+    exploration_hint_bonus: i32 = EXPLORATION_HINT_BONUS,
+    eject_handoff_gain: i32 = EJECT_HANDOFF_GAIN,
+    radar_min_unseen_cells: i32 = RADAR_MIN_UNSEEN_CELLS,
+    radar_min_base_distance: i32 = RADAR_MIN_BASE_DISTANCE,
+    memory_revisit_radius: i32 = MEMORY_REVISIT_RADIUS,
+    memory_uncertainty_turns: i32 = MEMORY_UNCERTAINTY_TURNS,
+    assignment_ghost_reward: i32 = ASSIGNMENT_GHOST_REWARD,
+    assignment_stun_reward: i32 = ASSIGNMENT_STUN_REWARD,
+    assignment_stun_carrier_reward: i32 = ASSIGNMENT_STUN_CARRIER_REWARD,
+    assignment_camper_penalty: i32 = ASSIGNMENT_CAMPER_PENALTY,
+    assignment_stun_armed_reward: i32 = ASSIGNMENT_STUN_ARMED_REWARD,
+    assignment_danger_penalty: i32 = ASSIGNMENT_DANGER_PENALTY,
+    assignment_commit_bonus: i32 = ASSIGNMENT_COMMIT_BONUS,
+    escort_reward: i32 = ESCORT_REWARD,
+    escort_threat_radius: i32 = ESCORT_THREAT_RADIUS,
+    escort_memory_turns: i32 = ESCORT_MEMORY_TURNS,
+    escort_offset: i32 = ESCORT_OFFSET,
+    route_memory_turns: i32 = ROUTE_MEMORY_TURNS,
+    route_danger_weight: i32 = ROUTE_DANGER_WEIGHT,
+    coop_target_bust_turns: i32 = COOP_TARGET_BUST_TURNS,
+    coop_shortfall_penalty: i32 = COOP_SHORTFALL_PENALTY,
+    contest_reinforce_turns: i32 = CONTEST_REINFORCE_TURNS,
+    use_search: bool = USE_SEARCH,
+    search_depth: i32 = SEARCH_DEPTH,
+    search_beam_width: i32 = SEARCH_BEAM_WIDTH,
+    search_budget_ms: i32 = SEARCH_BUDGET_MS,
+    search_override_margin: i32 = SEARCH_OVERRIDE_MARGIN,
+}
+
+// local runs take "--flag value" or the matching env var, the arena passes neither
+fn arg_value(args: &[String], flag: &str, env: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1).cloned())
+        .or_else(|| std::env::var(env).ok())
+}
+
+impl StrategyParams {
+    fn load() -> StrategyParams {
+        // arena runs get the defaults, local runs can point --config or CB_CONFIG at a
+        // "name = value" file and override single values with CB_PARAM_<NAME>
+        let mut params = StrategyParams::new();
+        let args: Vec<String> = std::env::args().collect();
+        let config_path = arg_value(&args, "--config", "CB_CONFIG");
+        let mut overridden = false;
+        if let Some(path) = config_path {
+            match std::fs::read_to_string(&path) {
+                Ok(contents) => {
+                    for line in contents.lines() {
+                        let line = line.split('#').next().unwrap().trim();
+                        if let Some((key, value)) = line.split_once('=') {
+                            params.apply(key.trim(), value.trim());
+                            overridden = true;
+                        }
+                    }
+                }
+                Err(err) => eprintln!("couldn't read config {}: {}", path, err),
+            }
+        }
+        for (key, value) in std::env::vars() {
+            if let Some(name) = key.strip_prefix("CB_PARAM_") {
+                params.apply(&name.to_lowercase(), value.trim());
+                overridden = true;
+            }
+        }
+        if overridden {
+            eprintln!("{}", params.to_config());
+        }
+        // the tuner asks for the starting point before it plays anything
+        if args.iter().any(|arg| arg == "--dump-params") {
+            println!("{}", params.to_config());
+            std::process::exit(0);
+        }
+        params
+    }
+    fn apply(&mut self, key: &str, value: &str) {
+        if !self.set(key, value) {
+            eprintln!("ignoring param {} = {}", key, value);
+        }
+    }
+}
 #[derive(Debug)]
 struct Coord {
     x: i32,
@@ -43,6 +245,10 @@ impl Coord {
     fn fake_new() -> Coord {
         Coord::new(-1, -1)
     }
+    fn mirrored(&self) -> Coord {
+        // ghosts spawn point symmetric around the middle of the map
+        Coord::new(MAP_RIGHT_WALL - self.x, MAP_BOTTOM_WALL - self.y)
+    }
 }
 
 impl PartialEq for Coord {
@@ -73,10 +279,33 @@ impl Coord {
     fn to_string(&self) -> String {
         return format!("{} {}", self.x, self.y);
     }
+    fn clamped(&self) -> Coord {
+        Coord::new(
+            self.x.clamp(0, MAP_RIGHT_WALL),
+            self.y.clamp(0, MAP_BOTTOM_WALL),
+        )
+    }
     fn set(&mut self, other: &Coord) -> () {
         self.x = other.x;
         self.y = other.y;
     }
+    fn step_towards(&self, other: &Self, distance: i32) -> Self {
+        let dist = self.distance_to(other);
+        if dist <= distance {
+            return other.clone();
+        }
+        self.interpolate(other, distance as f64 / dist as f64)
+    }
+    fn distance_to_segment(&self, start: &Coord, end: &Coord) -> i32 {
+        let (dx, dy) = ((end.x - start.x) as f64, (end.y - start.y) as f64);
+        let len_sq = dx * dx + dy * dy;
+        if len_sq == 0.0 {
+            return self.distance_to(start);
+        }
+        let t = (((self.x - start.x) as f64 * dx + (self.y - start.y) as f64 * dy) / len_sq)
+            .clamp(0.0, 1.0);
+        self.distance_to(&start.interpolate(end, t))
+    }
     fn interpolate(&self, other: &Self, factor: f64) -> Self {
         let x = self.x as f64 + factor * (other.x as f64 - self.x as f64);
         let y = self.y as f64 + factor * (other.y as f64 - self.y as f64);
@@ -87,7 +316,7 @@ impl Coord {
         count: usize,
         down_right: bool,
         radius: i32,
-        is_camping: bool,
+        throw_margin: f64,
     ) -> Vec<Coord> {
         let mut results = Vec::new();
 
@@ -111,11 +340,6 @@ impl Coord {
             };
 
             // Interpolate between the point on the circumference and the quadrant center
-            let throw_margin = if is_camping {
-                CAMPING_MARGIN
-            } else {
-                THROW_MARGIN
-            };
             let adjusted_point = point_on_circumference.interpolate(&quadrant_center, throw_margin);
 
             results.push(adjusted_point);
@@ -130,24 +354,45 @@ struct Ghost {
     entity_id: i32,
     coords: Coord,
     people_trapping: i32,
+    enemy_trapping: i32,
     stamina: i32,
+    last_seen_turn: i32,
+    visible: bool,
+    predicted_coords: Coord,
+    probable: bool, // never seen, guessed from the ghost on the other side of the map
 }
 
 impl Ghost {
     // constructors / updaters
-    fn new(id: i32, x: i32, y: i32, people_trapping: i32, stamina: i32) -> Rc<RefCell<Self>> {
-        Rc::new(RefCell::new(Ghost {
+    fn new(id: i32, x: i32, y: i32, people_trapping: i32, stamina: i32) -> Self {
+        Ghost {
             entity_id: id,
             coords: Coord::new(x, y),
             people_trapping,
+            enemy_trapping: 0,
             stamina,
-        }))
+            last_seen_turn: 0,
+            visible: true,
+            predicted_coords: Coord::new(x, y),
+            probable: false,
+        }
+    }
+    fn new_probable(source: &Ghost) -> Self {
+        let coords = source.coords.mirrored();
+        let mut probable = Ghost::new(-1 - source.entity_id, coords.x, coords.y, 0, source.stamina);
+        probable.last_seen_turn = source.last_seen_turn;
+        probable.visible = false;
+        probable.probable = true;
+        probable
     }
     fn transfer_ghost_data(&mut self, other: &Self) -> () {
         self.coords = Coord::new(other.coords.x, other.coords.y);
         self.people_trapping = other.people_trapping;
         self.stamina = other.stamina;
         self.people_trapping = other.people_trapping;
+        self.last_seen_turn = other.last_seen_turn;
+        self.visible = true;
+        self.predicted_coords = Coord::new(other.coords.x, other.coords.y);
     }
 }
 
@@ -160,7 +405,12 @@ impl Clone for Ghost {
                 y: self.coords.y,
             },
             people_trapping: self.people_trapping,
+            enemy_trapping: self.enemy_trapping,
             stamina: self.stamina,
+            last_seen_turn: self.last_seen_turn,
+            visible: self.visible,
+            predicted_coords: self.predicted_coords.clone(),
+            probable: self.probable,
         }
     }
 }
@@ -180,6 +430,47 @@ impl Ghost {
             .coords
             .is_in_radius(&buster.coords, INNER_BUSTING_RADIUS);
     }
+    fn busters_needed(&self, team_size: i32, target_bust_turns: i32) -> i32 {
+        // enough to finish in time, and always more than the other team has on it
+        let to_finish = (self.stamina + target_bust_turns - 1) / target_bust_turns;
+        to_finish.max(self.enemy_trapping + 1).min(team_size).max(1)
+    }
+    fn predict_flee(&self, buster_coords: &[Coord]) -> Coord {
+        // ghosts being trapped hold still, otherwise they run from the closest buster they see
+        if !self.visible || self.people_trapping > 0 {
+            return self.coords.clone();
+        }
+        let closest_dist = match buster_coords
+            .iter()
+            .map(|coords| self.coords.distance_to(coords))
+            .filter(|&dist| dist <= LINE_OF_SIGHT_RADIUS)
+            .min()
+        {
+            Some(dist) => dist,
+            None => return self.coords.clone(),
+        };
+        // equally close busters scare it away from their average position
+        let closest: Vec<&Coord> = buster_coords
+            .iter()
+            .filter(|coords| self.coords.distance_to(coords) == closest_dist)
+            .collect();
+        let avg_x =
+            closest.iter().map(|coords| coords.x as f64).sum::<f64>() / closest.len() as f64;
+        let avg_y =
+            closest.iter().map(|coords| coords.y as f64).sum::<f64>() / closest.len() as f64;
+        let dx = self.coords.x as f64 - avg_x;
+        let dy = self.coords.y as f64 - avg_y;
+        let len = (dx * dx + dy * dy).sqrt();
+        if len == 0.0 {
+            return self.coords.clone();
+        }
+        let flee_x = self.coords.x as f64 + dx / len * GHOST_FLEE_DISTANCE as f64;
+        let flee_y = self.coords.y as f64 + dy / len * GHOST_FLEE_DISTANCE as f64;
+        Coord::new(
+            (flee_x.round() as i32).clamp(0, MAP_RIGHT_WALL),
+            (flee_y.round() as i32).clamp(0, MAP_BOTTOM_WALL),
+        )
+    }
 }
 
 impl PartialEq for Ghost {
@@ -189,7 +480,7 @@ impl PartialEq for Ghost {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 enum BusterState {
     //buster states
     Idle,
@@ -210,61 +501,99 @@ enum BusterState {
     // in stunning range for person, wanting to stun
     MovingAwayFromGhost,
     // in ghost's inner circle, need to move away
+    UsingRadar,
+    // lots of fog around and radar still unused, take a look
+    EjectingToTeammate,
+    // teammate is a lot closer to home, throw them the ghost
+    EjectingToHome,
+    // getting chased, throw the ghost ahead and pick it back up
+    EjectingAwayFromAsshole,
+    // getting chased from the home side, throw the ghost away from them
+    Escorting,
+    // walking a teammate's ghost home, zapping anyone who gets close
+    Herding,
+    // ghost is between us and home, scare it there before busting
     Stunned,
 
     //camping states
     Camping,
     MovingToCamp,
+}
 
-    //opponent states
-    NotTargetted,
-    // this isn't used rn. maybe use for death mark
-    DeathMarked, // this isn't used rn. maybe use for death mark
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Intent {
+    Unknown,
+    // haven't seen them move yet
+    Returning,
+    // carrying a ghost home
+    Busting,
+    // parked on a ghost someone is trapping
+    Hunting,
+    // closing in on one of ours
+    Camping,
+    // sitting still near our base
+    Wandering,
+    // going somewhere that isn't us
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Buster {
     entity_id: i32,
     coords: Coord,
     state: BusterState,
-    busting_target_ref: Option<Rc<RefCell<Ghost>>>,
-    stunning_target_ref: Option<Rc<RefCell<Buster>>>,
+    busting_target_id: Option<i32>,
+    stunning_target_id: Option<i32>,
     movement_target: Coord,
     stun_timer: i32,
     has_ghost: bool,
+    carried_ghost_id: i32,
     is_stunned: bool,
+    has_radar: bool,
+    radar_turn: i32,
+    radar_coords: Coord,
+    eject_target: Coord,
+    eject_catcher_id: Option<i32>,
+    escorting_id: Option<i32>,
+    escort_return_state: BusterState,
+    escort_return_target: Coord,
 }
 
 impl Buster {
-    fn new(entity_id: i32, x: i32, y: i32, has_ghost: bool, is_stunned: bool) -> Rc<RefCell<Self>> {
-        Rc::new(RefCell::new(Buster {
+    fn new(
+        entity_id: i32,
+        x: i32,
+        y: i32,
+        has_ghost: bool,
+        is_stunned: bool,
+        carried_ghost_id: i32,
+    ) -> Self {
+        Buster {
             entity_id,
             coords: Coord::new(x, y),
             state: BusterState::Idle,
-            busting_target_ref: None,
-            stunning_target_ref: None,
+            busting_target_id: None,
+            stunning_target_id: None,
             movement_target: Coord::fake_new(),
             stun_timer: 0,
             has_ghost,
+            carried_ghost_id: if has_ghost { carried_ghost_id } else { -1 },
             is_stunned,
-        }))
+            has_radar: true,
+            radar_turn: -1,
+            radar_coords: Coord::fake_new(),
+            eject_target: Coord::fake_new(),
+            eject_catcher_id: None,
+            escorting_id: None,
+            escort_return_state: BusterState::Searching,
+            escort_return_target: Coord::fake_new(),
+        }
     }
     fn transfer_buster_data(&mut self, other: &Self) -> () {
         self.coords = Coord::new(other.coords.x, other.coords.y);
         self.has_ghost = other.has_ghost;
+        self.carried_ghost_id = other.carried_ghost_id;
         self.is_stunned = other.is_stunned;
     }
-    fn new_opponent(
-        entity_id: i32,
-        x: i32,
-        y: i32,
-        has_ghost: bool,
-        is_stunned: bool,
-    ) -> Rc<RefCell<Self>> {
-        let new_opp = Buster::new(entity_id, x, y, has_ghost, is_stunned);
-        new_opp.borrow_mut().state = BusterState::NotTargetted;
-        return new_opp;
-    }
 }
 
 impl Buster {
@@ -276,11 +605,6 @@ impl Buster {
     fn can_stun(&self) -> bool {
         return self.stun_timer == 0;
     }
-    fn is_stunnable(&self, buster: &Buster) -> bool {
-        return self
-            .coords
-            .is_in_radius(&buster.coords, OUTER_BUSTING_RADIUS);
-    }
 }
 
 impl PartialEq for Buster {
@@ -290,306 +614,3062 @@ impl PartialEq for Buster {
     }
 }
 
-impl Clone for Buster {
-    fn clone(&self) -> Buster {
-        Buster {
-            entity_id: self.entity_id,
-            coords: Coord::new(self.coords.x, self.coords.y),
-            state: self.state,
-            busting_target_ref: if let Some(ghost) = &self.busting_target_ref {
-                Some(Rc::clone(&ghost))
-            } else {
-                None
-            },
-            stunning_target_ref: if let Some(asshole) = &self.stunning_target_ref {
-                Some(Rc::clone(&asshole))
-            } else {
-                None
-            },
-            stun_timer: self.stun_timer,
-            movement_target: Coord::new(self.movement_target.x, self.movement_target.y),
-            has_ghost: self.has_ghost,
-            is_stunned: self.is_stunned,
+// what we know about one of theirs. our busters never run the FSM on these, a stun target is
+// reserved per turn instead so two of ours don't zap the same guy
+#[derive(Debug, Clone)]
+struct Opponent {
+    entity_id: i32,
+    coords: Coord,
+    has_ghost: bool,
+    carried_ghost_id: i32,
+    // -1 when empty handed
+    is_stunned: bool,
+    stun_timer: i32,
+    // our guess at their cooldown, set when we blame them for a stun
+    last_seen_turn: i32,
+    heading: Coord,
+    intent: Intent,
+    reserved_by: Option<i32>,
+    // which of ours gets to stun them this turn
+}
+
+impl Opponent {
+    fn new(
+        entity_id: i32,
+        x: i32,
+        y: i32,
+        has_ghost: bool,
+        is_stunned: bool,
+        carried_ghost_id: i32,
+    ) -> Self {
+        Opponent {
+            entity_id,
+            coords: Coord::new(x, y),
+            has_ghost,
+            carried_ghost_id: if has_ghost { carried_ghost_id } else { -1 },
+            is_stunned,
+            stun_timer: 0,
+            last_seen_turn: 0,
+            heading: Coord::new(0, 0),
+            intent: Intent::Unknown,
+            reserved_by: None,
+        }
+    }
+    fn transfer_opponent_data(&mut self, other: &Self) {
+        self.coords = Coord::new(other.coords.x, other.coords.y);
+        self.has_ghost = other.has_ghost;
+        self.carried_ghost_id = other.carried_ghost_id;
+        self.is_stunned = other.is_stunned;
+    }
+    fn tick(&mut self) {
+        if self.stun_timer > 0 {
+            self.stun_timer -= 1;
         }
     }
+    fn can_stun(&self) -> bool {
+        self.stun_timer == 0
+    }
+    fn is_dangerous(&self) -> bool {
+        // armed and coming for us rather than minding their own ghost
+        !self.is_stunned
+            && self.can_stun()
+            && matches!(self.intent, Intent::Hunting | Intent::Camping)
+    }
+    fn is_stunnable(&self, buster: &Buster) -> bool {
+        self.coords.is_in_radius(&buster.coords, STUN_RADIUS)
+    }
+    fn is_reserved_for_other(&self, buster: &Buster) -> bool {
+        self.reserved_by
+            .is_some_and(|buster_id| buster_id != buster.entity_id)
+    }
 }
 
-struct StateMachine {
-    upper_left_home: bool,
-    all_ghosts: Vec<Rc<RefCell<Ghost>>>,
-    good_busters: Vec<Rc<RefCell<Buster>>>,
-    bad_busters: Vec<Rc<RefCell<Buster>>>,
-    global_ping_stack: Vec<Rc<RefCell<Ghost>>>,
-    turn_count: i32,
+impl PartialEq for Opponent {
+    //is equivalent
+    fn eq(&self, other: &Opponent) -> bool {
+        self.entity_id == other.entity_id
+    }
 }
 
-impl StateMachine {
-    //other functions
-    fn throw_and_assign_coords(&self, is_camping: bool) -> () {
-        let num_busters = self.good_busters.len();
-        let origin = if self.upper_left_home != is_camping {
-            Coord::new(ORIGIN_THROWING_OFFSET, ORIGIN_THROWING_OFFSET)
-        } else {
-            Coord::new(
-                MAP_RIGHT_WALL - ORIGIN_THROWING_OFFSET,
-                MAP_BOTTOM_WALL - ORIGIN_THROWING_OFFSET,
-            )
-        };
-        let mut thrown_coords = origin.throw_coords(
-            num_busters,
-            if is_camping {
-                self.upper_left_home
-            } else {
-                !self.upper_left_home
-            },
-            if is_camping {
-                CAMPING_RADIUS
-            } else {
-                CAPTURE_RADIUS
-            },
-            is_camping,
-        );
+struct ExplorationGrid {
+    cols: i32,
+    rows: i32,
+    last_seen: Vec<i32>, // turn each cell was last in someone's line of sight, -1 for never
+}
 
-        for buster in &mut self.good_busters.iter() {
-            let mut best_index = None;
-            let mut min_dist = i32::MAX;
-            for (index, coord) in thrown_coords.iter().enumerate() {
-                let dist = buster.borrow().coords.distance_to(coord);
-                if dist < min_dist {
-                    best_index = Some(index);
-                    min_dist = dist;
+impl ExplorationGrid {
+    fn new() -> ExplorationGrid {
+        let cols = MAP_RIGHT_WALL / EXPLORATION_CELL_SIZE;
+        let rows = MAP_BOTTOM_WALL / EXPLORATION_CELL_SIZE;
+        ExplorationGrid {
+            cols,
+            rows,
+            last_seen: vec![-1; (cols * rows) as usize],
+        }
+    }
+    fn cell_center(&self, col: i32, row: i32) -> Coord {
+        Coord::new(
+            col * EXPLORATION_CELL_SIZE + EXPLORATION_CELL_SIZE / 2,
+            row * EXPLORATION_CELL_SIZE + EXPLORATION_CELL_SIZE / 2,
+        )
+    }
+    fn cell_of(&self, coords: &Coord) -> (i32, i32) {
+        (
+            (coords.x / EXPLORATION_CELL_SIZE).clamp(0, self.cols - 1),
+            (coords.y / EXPLORATION_CELL_SIZE).clamp(0, self.rows - 1),
+        )
+    }
+    fn is_seen_cell(&self, col: i32, row: i32) -> bool {
+        self.last_seen[(row * self.cols + col) as usize] >= 0
+    }
+    fn is_seen(&self, coords: &Coord) -> bool {
+        let (col, row) = self.cell_of(coords);
+        self.is_seen_cell(col, row)
+    }
+    fn has_unseen(&self) -> bool {
+        self.last_seen.iter().any(|&turn| turn < 0)
+    }
+    fn cells_in_radius(&self, center: &Coord, radius: i32) -> Vec<(i32, i32)> {
+        let (min_col, min_row) = self.cell_of(&Coord::new(center.x - radius, center.y - radius));
+        let (max_col, max_row) = self.cell_of(&Coord::new(center.x + radius, center.y + radius));
+        let mut cells = Vec::new();
+        for row in min_row..=max_row {
+            for col in min_col..=max_col {
+                if self.cell_center(col, row).is_in_radius(center, radius) {
+                    cells.push((col, row));
                 }
             }
-
-            if let Some(index) = best_index {
-                let best_coord = thrown_coords.remove(index);
-                buster
-                    .as_ref()
-                    .borrow_mut()
-                    .movement_target
-                    .set(&best_coord);
-            }
         }
+        cells
     }
-    fn set_new_buster_movement_coords(&self, buster: &mut Buster) -> () {
-        // lol its just a rng
-        let mut rng = rand::thread_rng();
-        let new_coords = Coord::new(
-            rng.gen_range(0..MAP_RIGHT_WALL),
-            rng.gen_range(0..MAP_BOTTOM_WALL),
-        );
-        buster.movement_target = new_coords;
+    fn mark_seen(&mut self, coords: &Coord, radius: i32, turn: i32) {
+        for (col, row) in self.cells_in_radius(coords, radius) {
+            self.last_seen[(row * self.cols + col) as usize] = turn;
+        }
     }
-    fn set_asshole_target(&self, asshole: &Rc<RefCell<Buster>>, buster: &mut Buster) -> () {
-        asshole.borrow_mut().state = BusterState::DeathMarked;
-        buster.stunning_target_ref = Some(Rc::clone(asshole));
-        if let Some(_) = &buster.busting_target_ref {
-            buster.busting_target_ref = None;
+    fn is_frontier(&self, col: i32, row: i32) -> bool {
+        // unseen, but next to something we've already looked at
+        if self.is_seen_cell(col, row) {
+            return false;
         }
+        [(-1, 0), (1, 0), (0, -1), (0, 1)].iter().any(|(dc, dr)| {
+            let (n_col, n_row) = (col + dc, row + dr);
+            n_col >= 0
+                && n_row >= 0
+                && n_col < self.cols
+                && n_row < self.rows
+                && self.is_seen_cell(n_col, n_row)
+        })
     }
-    fn find_ghost_in_radius_of_buster(&self, buster: &Buster) -> Option<&Rc<RefCell<Ghost>>> {
-        return self.all_ghosts.iter().find(|ghost| {
-            ghost
-                .borrow()
-                .coords
-                .is_in_radius(&buster.coords, LINE_OF_SIGHT_RADIUS)
-        });
+    fn unseen_gain(&self, center: &Coord, radius: i32) -> i32 {
+        self.cells_in_radius(center, radius)
+            .iter()
+            .filter(|&&(col, row)| !self.is_seen_cell(col, row))
+            .count() as i32
     }
-
-    fn do_global_ping(&mut self) {
-        let mut temp = Vec::new();
-        for buster_rc in &self.good_busters {
-            let buster = buster_rc.borrow();
-            if let Some(ref ghost_rc) = buster.busting_target_ref {
-                temp.push(Rc::clone(ghost_rc));
+    fn best_frontier(
+        &self,
+        from: &Coord,
+        teammates: &[Coord],
+        hints: &[Coord],
+        params: &StrategyParams,
+    ) -> Option<Coord> {
+        // what we'd uncover per turn spent walking, damped near where teammates are or are headed
+        let mut best = None;
+        let mut best_score = f64::MIN;
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                if !self.is_frontier(col, row) {
+                    continue;
+                }
+                let center = self.cell_center(col, row);
+                let travel_turns = (from.distance_to(&center) / BUSTER_MOVE_DISTANCE) as f64;
+                let spread = teammates
+                    .iter()
+                    .map(|coords| coords.distance_to(&center))
+                    .min()
+                    .map_or(1.0, |dist| {
+                        (dist as f64 / params.exploration_spread_radius as f64).min(1.0)
+                    });
+                // looking where a ghost probably is beats looking at empty fog
+                let hinted = hints
+                    .iter()
+                    .any(|hint| hint.is_in_radius(&center, LINE_OF_SIGHT_RADIUS));
+                let gain = self.unseen_gain(&center, LINE_OF_SIGHT_RADIUS)
+                    + if hinted {
+                        params.exploration_hint_bonus
+                    } else {
+                        0
+                    };
+                let score = gain as f64 / (1.0 + travel_turns) * spread;
+                if score > best_score {
+                    best_score = score;
+                    best = Some(center);
+                }
             }
         }
-        self.global_ping_stack = temp;
-    }
-
-    fn find_best_ghost_in_los(&self, buster: &Buster) -> Option<&Rc<RefCell<Ghost>>> {
-        self.all_ghosts
-            .iter()
-            .filter(|&ghost_rc| {
-                let ghost = ghost_rc.borrow();
-                buster.coords.distance_to(&ghost.coords) <= LINE_OF_SIGHT_RADIUS
-            })
-            .min_by_key(|&ghost_rc| {
-                let ghost = ghost_rc.borrow();
-                std::cmp::max(ghost.stamina, 3)
-            })
+        best
     }
-    fn find_best_asshole_in_los(&self, buster: &Buster) -> Option<Rc<RefCell<Buster>>> {
-        let within_radius_assholes: Vec<_> = self
-            .bad_busters
-            .iter()
-            .filter(|&buster_rc| {
-                let distance = buster.coords.distance_to(&buster_rc.borrow().coords);
-                distance <= LINE_OF_SIGHT_RADIUS
-                    && !matches!(buster_rc.borrow().state, BusterState::DeathMarked)
-                    && buster.can_stun()
-                    && !buster_rc.borrow().is_stunned
-            })
-            .collect();
+}
 
-        let asshole_with_ghost = within_radius_assholes
-            .iter()
-            .find(|&buster_rc| buster_rc.borrow().has_ghost);
+struct LaneMap {
+    heat: Vec<i32>, // carrier sightings per slice of the quarter circle around their base
+}
 
-        match asshole_with_ghost {
-            Some(asshole) => Some(Rc::clone(asshole)),
-            None => within_radius_assholes
-                .get(0)
-                .map(|&buster_rc| Rc::clone(buster_rc)),
+impl LaneMap {
+    fn new() -> LaneMap {
+        LaneMap {
+            heat: vec![0; LANE_BINS],
         }
     }
-    fn steal_target_ghost_reference(&self, buster: &Buster) -> Option<Rc<RefCell<Ghost>>> {
-        let mut closest_ghost: Option<Rc<RefCell<Ghost>>> = None;
-        let mut min_dist = i32::MAX;
-        if self.turn_count < TURNS_UNTIL_GLOBAL_PICKS {
+    fn bin_of(&self, angle: f64) -> usize {
+        ((angle / std::f64::consts::FRAC_PI_2 * LANE_BINS as f64) as usize).min(LANE_BINS - 1)
+    }
+    fn bin_angle(&self, bin: usize) -> f64 {
+        (bin as f64 + 0.5) / LANE_BINS as f64 * std::f64::consts::FRAC_PI_2
+    }
+    fn record(&mut self, angle: f64) {
+        let bin = self.bin_of(angle);
+        self.heat[bin] += 1;
+    }
+    fn hottest_angles(&self, count: usize, min_sightings: i32) -> Option<Vec<f64>> {
+        // busiest lanes first, skipping right next to one that's already covered
+        if self.heat.iter().sum::<i32>() < min_sightings {
             return None;
         }
-        // Iterating through the global ping stack to find the closest ghost
-        for target_ghost_rc in &self.global_ping_stack {
-            let distance = buster.coords.distance_to(&target_ghost_rc.borrow().coords);
-            if distance < min_dist {
-                closest_ghost = Some(Rc::clone(target_ghost_rc));
-                min_dist = distance;
-            }
+        let mut covered = [false; LANE_BINS];
+        let mut angles = Vec::new();
+        while angles.len() < count {
+            let best = (0..LANE_BINS)
+                .filter(|&bin| !covered[bin])
+                .max_by_key(|&bin| self.heat[bin]);
+            let bin = match best {
+                Some(bin) => bin,
+                None => break,
+            };
+            let spread =
+                bin.saturating_sub(LANE_SPREAD_BINS)..=(bin + LANE_SPREAD_BINS).min(LANE_BINS - 1);
+            covered[spread].fill(true);
+            angles.push(self.bin_angle(bin));
         }
-        closest_ghost
-    }
-    fn ghost_difficulty_test(&self, ghost: &Ghost) -> bool {
-        eprintln!("{:?}", ghost);
-        return match ghost.stamina {
-            x if x > 15 => {
-                eprintln!("made it here");
-                eprintln!("{}", self.turn_count >= TURNS_UNTIL_GLOBAL_PICKS);
-                self.turn_count >= TURNS_UNTIL_GLOBAL_PICKS
-            }
-            _ => true,
-        };
+        // not enough separate lanes, double up on the busiest ones
+        let found = angles.len();
+        for index in 0..count.saturating_sub(found) {
+            angles.push(angles[index % found]);
+        }
+        Some(angles)
     }
 }
 
-impl StateMachine {
-    //constructors and updators
-    fn new(team_id: i32) -> StateMachine {
-        StateMachine {
-            upper_left_home: team_id == 0,
-            all_ghosts: Vec::new(),
-            good_busters: Vec::new(),
-            bad_busters: Vec::new(),
-            global_ping_stack: Vec::new(),
-            turn_count: 0,
-        }
-    }
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Assignment {
+    Nothing,
+    Ghost(usize),   // index into all_ghosts
+    Asshole(usize), // index into bad_busters
+    Escort(usize),  // index into good_busters
+}
 
-    fn update_ghosts(&mut self, new_ghosts: Vec<Rc<RefCell<Ghost>>>) {
-        let mut to_remove = Vec::new();
+#[derive(Debug)]
+struct PlanEntry {
+    buster_id: i32,
+    ghost: Option<i32>,
+    asshole: Option<i32>,
+    escort: Option<i32>,
+}
 
-        // Update data for old ghosts and mark absent old ghosts for removal
-        for (index, old_ghost_rc) in self.all_ghosts.iter().enumerate() {
-            if new_ghosts
-                .iter()
-                .find(|&new_ghost_rc| old_ghost_rc.borrow().eq(&new_ghost_rc.borrow()))
-                .is_none()
-            {
-                to_remove.push(index);
-            }
-        }
+struct PlanBuster {
+    entity_id: i32,
+    coords: Coord,
+    is_camper: bool,
+    can_stun: bool,
+    options: Vec<Assignment>,
+    // what it's already on, switching away costs the ground it's made
+    current: Assignment,
+    // the most any one of its options can take off the team cost
+    max_gain: i32,
+}
 
-        // Remove old ghosts not present in new_ghosts
-        for index in to_remove.into_iter().rev() {
-            let ghost = &self.all_ghosts[index];
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Strategy {
+    Normal,
+    // still all to play for
+    Turtle,
+    // what we carry wins it, just get it home safe
+    Raid,
+    // can't win on ghosts left, go take theirs
+}
 
-            // Check if this ghost is being targeted by any good_buster
-            for buster_rc in &self.good_busters {
-                let mut buster = buster_rc.borrow_mut();
-                if let Some(target) = &buster.busting_target_ref {
-                    if Rc::ptr_eq(target, ghost) {
-                        buster.busting_target_ref = None;
-                    }
-                }
+const FSM_MAX_SLIDE_STEPS: usize = 12;
+
+// one way out of a state: if the guard holds the action runs and the buster lands in `to`.
+// rules out of the same state are tried in table order
+struct TransitionRule {
+    from: BusterState,
+    to: BusterState,
+    name: &'static str,
+    guard: fn(&StateMachine, &Buster) -> bool,
+    action: fn(&mut StateMachine, &mut Buster) -> (),
+}
+
+// how a state gets left: exit says when to look at the rules at all, prepare tidies the
+// buster up before they're tried and fallback is where it goes if none of them match
+struct StateSpec {
+    state: BusterState,
+    exit_name: &'static str,
+    exit: fn(&StateMachine, &Buster) -> bool,
+    prepare: fn(&mut StateMachine, &mut Buster) -> (),
+    fallback: BusterState,
+}
+
+fn always(_: &StateMachine, _: &Buster) -> bool {
+    true
+}
+fn nothing(_: &mut StateMachine, _: &mut Buster) {}
+
+const STATE_SPECS: &[StateSpec] = &[
+    StateSpec {
+        state: BusterState::Idle,
+        exit_name: "always",
+        exit: always,
+        prepare: nothing,
+        fallback: BusterState::Searching,
+    },
+    StateSpec {
+        state: BusterState::Searching,
+        exit_name: "searching_test",
+        exit: |machine, buster| machine.searching_test(buster),
+        prepare: nothing,
+        fallback: BusterState::Searching,
+    },
+    StateSpec {
+        state: BusterState::MovingToGhost,
+        exit_name: "ghost_moving_test",
+        exit: |machine, buster| machine.ghost_moving_test(buster),
+        prepare: |machine, buster| {
+            if machine.planned_ghost(buster).is_none() {
+                buster.busting_target_id = None;
             }
-            self.all_ghosts.remove(index);
-        }
+        },
+        fallback: BusterState::Searching,
+    },
+    StateSpec {
+        state: BusterState::Busting,
+        exit_name: "busting_test",
+        exit: |machine, buster| machine.busting_test(buster),
+        prepare: nothing,
+        fallback: BusterState::Searching,
+    },
+    StateSpec {
+        state: BusterState::MovingToHome,
+        exit_name: "house_move_test",
+        exit: |machine, buster| machine.house_move_test(buster),
+        prepare: nothing,
+        fallback: BusterState::MovingToHome,
+    },
+    StateSpec {
+        state: BusterState::Releasing,
+        exit_name: "release_test",
+        exit: |machine, buster| machine.release_test(buster),
+        prepare: nothing,
+        fallback: BusterState::Searching,
+    },
+    StateSpec {
+        state: BusterState::MovingToAsshole,
+        exit_name: "asshole_moving_test",
+        exit: |machine, buster| machine.asshole_moving_test(buster),
+        prepare: nothing,
+        fallback: BusterState::Searching,
+    },
+    StateSpec {
+        state: BusterState::Stunning,
+        exit_name: "stunning_test",
+        exit: |machine, buster| machine.stunning_test(buster),
+        prepare: nothing,
+        fallback: BusterState::Searching,
+    },
+    StateSpec {
+        state: BusterState::MovingAwayFromGhost,
+        exit_name: "ghost_moving_away_test",
+        exit: |machine, buster| machine.ghost_moving_away_test(buster),
+        prepare: |machine, buster| buster.busting_target_id = machine.planned_ghost(buster),
+        fallback: BusterState::Searching,
+    },
+    StateSpec {
+        state: BusterState::UsingRadar,
+        exit_name: "radar_used",
+        exit: |_, buster| !buster.has_radar,
+        prepare: nothing,
+        fallback: BusterState::Searching,
+    },
+    StateSpec {
+        state: BusterState::EjectingToTeammate,
+        exit_name: "ghost_thrown",
+        exit: |_, buster| !buster.has_ghost,
+        prepare: nothing,
+        fallback: BusterState::Searching,
+    },
+    StateSpec {
+        state: BusterState::EjectingToHome,
+        exit_name: "ghost_thrown",
+        exit: |_, buster| !buster.has_ghost,
+        prepare: nothing,
+        fallback: BusterState::Searching,
+    },
+    StateSpec {
+        state: BusterState::EjectingAwayFromAsshole,
+        exit_name: "ghost_thrown",
+        exit: |_, buster| !buster.has_ghost,
+        prepare: nothing,
+        fallback: BusterState::Searching,
+    },
+    StateSpec {
+        state: BusterState::Escorting,
+        exit_name: "escorting_test",
+        exit: |machine, buster| machine.escorting_test(buster),
+        prepare: nothing,
+        fallback: BusterState::Searching,
+    },
+    StateSpec {
+        state: BusterState::Herding,
+        exit_name: "herding_test",
+        exit: |machine, buster| machine.herding_test(buster),
+        prepare: nothing,
+        fallback: BusterState::MovingToGhost,
+    },
+    StateSpec {
+        state: BusterState::Stunned,
+        exit_name: "stunned_test",
+        exit: |machine, buster| machine.stunned_test(buster),
+        prepare: nothing,
+        fallback: BusterState::Searching,
+    },
+    StateSpec {
+        state: BusterState::Camping,
+        exit_name: "camping_test",
+        exit: |machine, buster| machine.camping_test(buster),
+        prepare: nothing,
+        fallback: BusterState::Camping,
+    },
+    StateSpec {
+        state: BusterState::MovingToCamp,
+        exit_name: "camp_moving_test",
+        exit: |machine, buster| machine.camp_moving_test(buster),
+        prepare: nothing,
+        fallback: BusterState::MovingToCamp,
+    },
+];
 
-        for new_ghost_rc in new_ghosts.iter() {
-            if self
-                .all_ghosts
-                .iter()
-                .find(|&old_ghost_rc| old_ghost_rc.borrow().eq(&new_ghost_rc.borrow()))
-                .is_none()
-            {
-                self.all_ghosts.push(new_ghost_rc.clone());
-            } else {
-                let old_ghost_rc = self
-                    .all_ghosts
-                    .iter()
-                    .find(|&old_ghost_rc| old_ghost_rc.borrow().eq(&new_ghost_rc.borrow()))
-                    .unwrap();
-                old_ghost_rc
-                    .borrow_mut()
-                    .transfer_ghost_data(&new_ghost_rc.borrow());
+const TRANSITION_RULES: &[TransitionRule] = &[
+    TransitionRule {
+        from: BusterState::Idle,
+        to: BusterState::Searching,
+        name: "first turn",
+        guard: always,
+        action: nothing,
+    },
+    //searching
+    TransitionRule {
+        from: BusterState::Searching,
+        to: BusterState::MovingToCamp,
+        name: "camping time",
+        guard: |machine, _| machine.camping_time(),
+        action: nothing,
+    },
+    TransitionRule {
+        from: BusterState::Searching,
+        to: BusterState::Searching,
+        name: "target reached",
+        guard: |machine, buster| {
+            buster.coords.eq(&buster.movement_target) || machine.target_explored(buster)
+        },
+        action: |machine, buster| machine.set_new_buster_movement_coords(buster),
+    },
+    TransitionRule {
+        from: BusterState::Searching,
+        to: BusterState::MovingToAsshole,
+        name: "asshole planned",
+        guard: |machine, buster| machine.planned_asshole(buster).is_some(),
+        action: |machine, buster| machine.take_planned_asshole(buster),
+    },
+    TransitionRule {
+        from: BusterState::Searching,
+        to: BusterState::MovingToGhost,
+        name: "ghost planned",
+        guard: |machine, buster| machine.planned_ghost(buster).is_some(),
+        action: |machine, buster| machine.take_planned_ghost(buster),
+    },
+    TransitionRule {
+        from: BusterState::Searching,
+        to: BusterState::Escorting,
+        name: "escort planned",
+        guard: |machine, buster| machine.planned_escort(buster).is_some(),
+        action: |machine, buster| machine.start_escort(buster),
+    },
+    TransitionRule {
+        from: BusterState::Searching,
+        to: BusterState::UsingRadar,
+        name: "radar time",
+        guard: |machine, buster| machine.radar_test(buster),
+        action: nothing,
+    },
+    //moving to ghost
+    TransitionRule {
+        from: BusterState::MovingToGhost,
+        to: BusterState::MovingToGhost,
+        name: "ghost replanned",
+        guard: |machine, buster| {
+            machine.planned_ghost(buster).is_some() && !machine.is_planned_ghost(buster)
+        },
+        action: |machine, buster| machine.take_planned_ghost(buster),
+    },
+    TransitionRule {
+        from: BusterState::MovingToGhost,
+        to: BusterState::MovingToAsshole,
+        name: "asshole planned",
+        guard: |machine, buster| machine.planned_asshole(buster).is_some(),
+        action: |machine, buster| machine.take_planned_asshole(buster),
+    },
+    TransitionRule {
+        from: BusterState::MovingToGhost,
+        to: BusterState::Herding,
+        name: "herding pays",
+        guard: |machine, buster| {
+            machine
+                .busting_target(buster)
+                .is_some_and(|ghost| machine.herding_pays(buster, ghost))
+        },
+        action: nothing,
+    },
+    TransitionRule {
+        from: BusterState::MovingToGhost,
+        to: BusterState::MovingAwayFromGhost,
+        name: "too close",
+        guard: |machine, buster| {
+            machine
+                .busting_target(buster)
+                .is_some_and(|ghost| ghost.is_too_close(buster))
+        },
+        action: nothing,
+    },
+    TransitionRule {
+        from: BusterState::MovingToGhost,
+        to: BusterState::Busting,
+        name: "in range",
+        guard: |_, buster| buster.busting_target_id.is_some(),
+        action: nothing,
+    },
+    TransitionRule {
+        from: BusterState::MovingToGhost,
+        to: BusterState::Searching,
+        name: "ghost gone",
+        guard: always,
+        action: nothing,
+    },
+    //busting
+    TransitionRule {
+        from: BusterState::Busting,
+        to: BusterState::MovingToHome,
+        name: "got it",
+        guard: |_, buster| buster.has_ghost,
+        action: nothing,
+    },
+    TransitionRule {
+        from: BusterState::Busting,
+        to: BusterState::MovingToAsshole,
+        name: "asshole planned",
+        guard: |machine, buster| machine.planned_asshole(buster).is_some(),
+        action: |machine, buster| machine.take_planned_asshole(buster),
+    },
+    TransitionRule {
+        from: BusterState::Busting,
+        to: BusterState::Searching,
+        name: "ghost gone",
+        guard: always,
+        action: nothing,
+    },
+    //moving home
+    TransitionRule {
+        from: BusterState::MovingToHome,
+        to: BusterState::Searching,
+        name: "lost the ghost",
+        guard: |_, buster| !buster.has_ghost,
+        action: nothing,
+    },
+    TransitionRule {
+        from: BusterState::MovingToHome,
+        to: BusterState::Releasing,
+        name: "made it home",
+        guard: |machine, buster| {
+            machine
+                .home_coords()
+                .is_in_radius(&buster.coords, BASE_RELEASE_RADIUS)
+        },
+        action: nothing,
+    },
+    TransitionRule {
+        from: BusterState::MovingToHome,
+        to: BusterState::EjectingToTeammate,
+        name: "teammate closer",
+        guard: |machine, buster| {
+            matches!(
+                machine.plan_eject(buster),
+                Some((BusterState::EjectingToTeammate, _))
+            )
+        },
+        action: |machine, buster| machine.take_eject_target(buster),
+    },
+    TransitionRule {
+        from: BusterState::MovingToHome,
+        to: BusterState::EjectingToHome,
+        name: "chased",
+        guard: |machine, buster| {
+            matches!(
+                machine.plan_eject(buster),
+                Some((BusterState::EjectingToHome, _))
+            )
+        },
+        action: |machine, buster| machine.take_eject_target(buster),
+    },
+    TransitionRule {
+        from: BusterState::MovingToHome,
+        to: BusterState::EjectingAwayFromAsshole,
+        name: "cut off",
+        guard: |machine, buster| {
+            matches!(
+                machine.plan_eject(buster),
+                Some((BusterState::EjectingAwayFromAsshole, _))
+            )
+        },
+        action: |machine, buster| machine.take_eject_target(buster),
+    },
+    //releasing, radar and throwing all go back to looking
+    TransitionRule {
+        from: BusterState::Releasing,
+        to: BusterState::Searching,
+        name: "released",
+        guard: always,
+        action: nothing,
+    },
+    TransitionRule {
+        from: BusterState::UsingRadar,
+        to: BusterState::Searching,
+        name: "pinged",
+        guard: always,
+        action: nothing,
+    },
+    TransitionRule {
+        from: BusterState::EjectingToTeammate,
+        to: BusterState::Searching,
+        name: "thrown",
+        guard: always,
+        action: |_, buster| buster.busting_target_id = None,
+    },
+    TransitionRule {
+        from: BusterState::EjectingToHome,
+        to: BusterState::Searching,
+        name: "thrown",
+        guard: always,
+        action: |_, buster| buster.busting_target_id = None,
+    },
+    TransitionRule {
+        from: BusterState::EjectingAwayFromAsshole,
+        to: BusterState::Searching,
+        name: "thrown",
+        guard: always,
+        action: |_, buster| buster.busting_target_id = None,
+    },
+    //moving to asshole
+    TransitionRule {
+        from: BusterState::MovingToAsshole,
+        to: BusterState::Escorting,
+        name: "back to escort",
+        guard: |machine, buster| machine.escort_detour_over(buster),
+        action: |_, buster| buster.stunning_target_id = None,
+    },
+    TransitionRule {
+        from: BusterState::MovingToAsshole,
+        to: BusterState::Searching,
+        name: "teammate has them",
+        guard: |machine, buster| {
+            machine
+                .stunning_target(buster)
+                .is_some_and(|asshole| asshole.is_reserved_for_other(buster))
+        },
+        action: |_, buster| buster.stunning_target_id = None,
+    },
+    TransitionRule {
+        from: BusterState::MovingToAsshole,
+        to: BusterState::Stunning,
+        name: "in range",
+        guard: |_, buster| buster.stunning_target_id.is_some(),
+        action: nothing,
+    },
+    TransitionRule {
+        from: BusterState::MovingToAsshole,
+        to: BusterState::Searching,
+        name: "asshole gone",
+        guard: always,
+        action: nothing,
+    },
+    //stunning
+    TransitionRule {
+        from: BusterState::Stunning,
+        to: BusterState::Escorting,
+        name: "back to escort",
+        guard: |machine, buster| machine.escort_detour_over(buster),
+        action: |_, buster| buster.stunning_target_id = None,
+    },
+    TransitionRule {
+        from: BusterState::Stunning,
+        to: BusterState::Searching,
+        name: "teammate has them",
+        guard: |machine, buster| {
+            machine
+                .stunning_target(buster)
+                .is_some_and(|asshole| asshole.is_reserved_for_other(buster))
+        },
+        action: |_, buster| buster.stunning_target_id = None,
+    },
+    TransitionRule {
+        from: BusterState::Stunning,
+        to: BusterState::Searching,
+        name: "stun used",
+        guard: |_, buster| !buster.can_stun(),
+        action: nothing,
+    },
+    TransitionRule {
+        from: BusterState::Stunning,
+        to: BusterState::Searching,
+        name: "zapped",
+        guard: |_, buster| buster.stunning_target_id.is_some(),
+        action: |_, buster| buster.stunning_target_id = None,
+    },
+    TransitionRule {
+        from: BusterState::Stunning,
+        to: BusterState::MovingToAsshole,
+        name: "no target",
+        guard: always,
+        action: nothing,
+    },
+    //moving away from ghost
+    TransitionRule {
+        from: BusterState::MovingAwayFromGhost,
+        to: BusterState::MovingToAsshole,
+        name: "asshole planned",
+        guard: |machine, buster| machine.planned_asshole(buster).is_some(),
+        action: |machine, buster| machine.take_planned_asshole(buster),
+    },
+    TransitionRule {
+        from: BusterState::MovingAwayFromGhost,
+        to: BusterState::Busting,
+        name: "back in range",
+        guard: |machine, buster| {
+            machine
+                .busting_target(buster)
+                .is_some_and(|ghost| ghost.is_bustable(buster))
+        },
+        action: nothing,
+    },
+    TransitionRule {
+        from: BusterState::MovingAwayFromGhost,
+        to: BusterState::MovingToGhost,
+        name: "backed off too far",
+        guard: |_, buster| buster.busting_target_id.is_some(),
+        action: nothing,
+    },
+    TransitionRule {
+        from: BusterState::MovingAwayFromGhost,
+        to: BusterState::Searching,
+        name: "ghost gone",
+        guard: always,
+        action: nothing,
+    },
+    //herding
+    TransitionRule {
+        from: BusterState::Herding,
+        to: BusterState::MovingToGhost,
+        name: "done herding",
+        guard: always,
+        action: nothing,
+    },
+    //escorting
+    TransitionRule {
+        from: BusterState::Escorting,
+        to: BusterState::MovingToAsshole,
+        name: "asshole closing in",
+        guard: |machine, buster| machine.escort_stun_target(buster).is_some(),
+        action: |machine, buster| {
+            if let Some(asshole) = machine.escort_stun_target(buster) {
+                machine.set_asshole_target(asshole, buster);
             }
+        },
+    },
+    TransitionRule {
+        from: BusterState::Escorting,
+        to: BusterState::Escorting,
+        name: "carrier replanned",
+        guard: |machine, buster| machine.planned_escort(buster).is_some(),
+        action: |machine, buster| buster.escorting_id = machine.planned_escort(buster),
+    },
+    TransitionRule {
+        from: BusterState::Escorting,
+        to: BusterState::MovingToCamp,
+        name: "back to camp",
+        guard: |machine, buster| {
+            machine.camping_time()
+                && matches!(buster.escort_return_state, BusterState::MovingToCamp)
+        },
+        action: |machine, buster| machine.end_escort(buster),
+    },
+    TransitionRule {
+        from: BusterState::Escorting,
+        to: BusterState::Searching,
+        name: "carrier home",
+        guard: always,
+        action: |machine, buster| machine.end_escort(buster),
+    },
+    //stunned
+    TransitionRule {
+        from: BusterState::Stunned,
+        to: BusterState::Searching,
+        name: "woke up",
+        guard: always,
+        action: nothing,
+    },
+    //camping
+    TransitionRule {
+        from: BusterState::MovingToCamp,
+        to: BusterState::Camping,
+        name: "at camp",
+        guard: |_, buster| buster.coords.eq(&buster.movement_target),
+        action: nothing,
+    },
+    TransitionRule {
+        from: BusterState::MovingToCamp,
+        to: BusterState::MovingToGhost,
+        name: "ghost planned",
+        guard: |machine, buster| machine.planned_ghost(buster).is_some(),
+        action: |machine, buster| machine.take_planned_ghost(buster),
+    },
+    TransitionRule {
+        from: BusterState::MovingToCamp,
+        to: BusterState::MovingToAsshole,
+        name: "asshole planned",
+        guard: |machine, buster| machine.planned_asshole(buster).is_some(),
+        action: |machine, buster| machine.take_planned_asshole(buster),
+    },
+    TransitionRule {
+        from: BusterState::MovingToCamp,
+        to: BusterState::Escorting,
+        name: "escort planned",
+        guard: |machine, buster| machine.planned_escort(buster).is_some(),
+        action: |machine, buster| machine.start_escort(buster),
+    },
+    TransitionRule {
+        from: BusterState::Camping,
+        to: BusterState::MovingToGhost,
+        name: "ghost planned",
+        guard: |machine, buster| machine.planned_ghost(buster).is_some(),
+        action: |machine, buster| machine.take_planned_ghost(buster),
+    },
+    TransitionRule {
+        from: BusterState::Camping,
+        to: BusterState::MovingToAsshole,
+        name: "asshole planned",
+        guard: |machine, buster| machine.planned_asshole(buster).is_some(),
+        action: |machine, buster| machine.take_planned_asshole(buster),
+    },
+    TransitionRule {
+        from: BusterState::Camping,
+        to: BusterState::Escorting,
+        name: "escort planned",
+        guard: |machine, buster| machine.planned_escort(buster).is_some(),
+        action: |machine, buster| machine.start_escort(buster),
+    },
+];
+
+fn state_spec(state: BusterState) -> Option<&'static StateSpec> {
+    STATE_SPECS.iter().find(|spec| spec.state == state)
+}
+
+// reports tables that could strand a buster instead of finding out mid match
+fn check_fsm() -> Vec<String> {
+    let mut problems = Vec::new();
+    for spec in STATE_SPECS.iter() {
+        let rules = TRANSITION_RULES
+            .iter()
+            .filter(|rule| rule.from == spec.state)
+            .count();
+        if rules == 0 {
+            problems.push(format!("{:?} has no rules out", spec.state));
+        }
+        if state_spec(spec.fallback).is_none() {
+            problems.push(format!(
+                "{:?} falls back to {:?} which has no spec",
+                spec.state, spec.fallback
+            ));
         }
     }
-
-    fn update_good(&mut self, new_busters: Vec<Rc<RefCell<Buster>>>) -> () {
-        for old_buster_rc in self.good_busters.iter() {
-            if let Some(new_buster_rc) = new_busters
-                .iter()
-                .find(|&new_buster_rc| old_buster_rc.borrow().eq(&new_buster_rc.borrow()))
-            {
-                old_buster_rc
-                    .borrow_mut()
-                    .transfer_buster_data(&new_buster_rc.borrow());
+    for rule in TRANSITION_RULES.iter() {
+        for state in [rule.from, rule.to] {
+            if state_spec(state).is_none() {
+                problems.push(format!(
+                    "rule \"{}\" uses {:?} which has no spec",
+                    rule.name, state
+                ));
             }
         }
     }
+    problems
+}
 
-    fn update_evil(&mut self, new_busters: Vec<Rc<RefCell<Buster>>>) -> () {
-        // Indices of bad busters that need to be removed
-        let mut to_remove = Vec::new();
-
-        for (index, old_buster_rc) in self.bad_busters.iter().enumerate() {
-            if let Some(new_buster_rc) = new_busters
-                .iter()
-                .find(|&new_buster_rc| old_buster_rc.borrow().eq(&new_buster_rc.borrow()))
-            {
-                old_buster_rc
-                    .borrow_mut()
-                    .transfer_buster_data(&new_buster_rc.borrow());
-            } else {
-                to_remove.push(index);
-            }
-        }
+struct TraceStep {
+    from: BusterState,
+    guard: &'static str,
+    to: BusterState,
+}
 
-        for index in to_remove.into_iter().rev() {
-            let evil_buster = &self.bad_busters[index];
+// everything one buster went through in one state_slide
+struct SlideTrace {
+    turn: i32,
+    buster_id: i32,
+    steps: Vec<TraceStep>,
+    looped: bool,
+}
 
-            for good_buster_rc in &self.good_busters {
-                let mut good_buster = good_buster_rc.borrow_mut();
-                if let Some(stunning_target_ref) = &good_buster.stunning_target_ref {
-                    if Rc::ptr_eq(stunning_target_ref, evil_buster) {
-                        good_buster.stunning_target_ref = None;
-                    }
-                }
+impl SlideTrace {
+    fn new(turn: i32, buster_id: i32) -> SlideTrace {
+        SlideTrace {
+            turn,
+            buster_id,
+            steps: Vec::new(),
+            looped: false,
+        }
+    }
+    fn push(&mut self, step: TraceStep) {
+        // staying put is a retarget, doing it twice or coming back to an earlier state is a loop
+        let self_hop = step.from == step.to;
+        let landed_before = self
+            .steps
+            .iter()
+            .any(|old| old.to == step.to && (!self_hop || old.from == step.to));
+        let back_to_start = !self_hop
+            && self
+                .steps
+                .first()
+                .is_some_and(|first| first.from == step.to);
+        if landed_before || back_to_start {
+            self.looped = true;
+        }
+        self.steps.push(step);
+    }
+    fn describe(&self) -> String {
+        let mut chain = match self.steps.first() {
+            Some(first) => format!("{:?}", first.from),
+            None => String::new(),
+        };
+        for step in self.steps.iter() {
+            chain += &format!(" -[{}]-> {:?}", step.guard, step.to);
+        }
+        format!(
+            "{} turn {}: {}{}",
+            self.buster_id,
+            self.turn,
+            chain,
+            if self.looped { " LOOP" } else { "" }
+        )
+    }
+    fn to_dot(&self) -> String {
+        let color = if self.looped { "red" } else { "black" };
+        let mut dot = format!(
+            "digraph turn_{}_buster_{} {{\n    label=\"{}\";\n",
+            self.turn,
+            self.buster_id,
+            self.describe()
+        );
+        for (index, step) in self.steps.iter().enumerate() {
+            dot += &format!(
+                "    {:?} -> {:?} [label=\"{}. {}\" color={}];\n",
+                step.from,
+                step.to,
+                index + 1,
+                step.guard,
+                color
+            );
+        }
+        dot += "}\n";
+        dot
+    }
+}
+
+// --trace PATH appends every slide that went anywhere as its own digraph, --dump-fsm prints
+// the whole transition graph and quits, debug builds and --check-fsm look the tables over
+struct FsmTrace {
+    file: Option<std::fs::File>,
+}
+
+impl FsmTrace {
+    fn open() -> FsmTrace {
+        let args: Vec<String> = std::env::args().collect();
+        if args.iter().any(|arg| arg == "--dump-fsm") {
+            print!("{}", FsmTrace::graph_dot());
+            std::process::exit(0);
+        }
+        if cfg!(debug_assertions) || args.iter().any(|arg| arg == "--check-fsm") {
+            for problem in check_fsm() {
+                eprintln!("fsm check: {}", problem);
+            }
+        }
+        let file = arg_value(&args, "--trace", "CB_TRACE").and_then(|path| {
+            std::fs::File::create(&path)
+                .map_err(|err| eprintln!("couldn't trace to {}: {}", path, err))
+                .ok()
+        });
+        FsmTrace { file }
+    }
+    fn graph_dot() -> String {
+        let mut dot = String::from("digraph buster_fsm {\n");
+        for rule in TRANSITION_RULES.iter() {
+            dot += &format!(
+                "    {:?} -> {:?} [label=\"{}\"];\n",
+                rule.from, rule.to, rule.name
+            );
+        }
+        for spec in STATE_SPECS.iter() {
+            dot += &format!(
+                "    {:?} -> {:?} [label=\"fallback\" style=dashed];\n",
+                spec.state, spec.fallback
+            );
+        }
+        dot += "}\n";
+        dot
+    }
+    fn is_on(&self) -> bool {
+        self.file.is_some()
+    }
+    fn note(&self, line: &str) {
+        // a DOT comment, so the file still parses with whatever else went on in the turn
+        if let Some(mut file) = self.file.as_ref() {
+            let _ = file.write_all(format!("// {}\n", line).as_bytes());
+        }
+    }
+    fn record(&mut self, slide: &SlideTrace) {
+        if slide.steps.is_empty() {
+            return;
+        }
+        if let Some(file) = &mut self.file {
+            eprintln!("{}", slide.describe());
+            let _ = file.write_all(slide.to_dot().as_bytes());
+        }
+    }
+}
+
+// the entities as of this turn, plain data with everything pointing at each other by id so
+// the whole lot can be copied and played forward
+#[derive(Debug, Clone)]
+struct GameState {
+    turn_count: i32,
+    our_score: i32,
+    enemy_score: i32,
+    ghosts: Vec<Ghost>,
+    busters: Vec<Buster>,
+    opponents: Vec<Opponent>,
+    enemy_memory: Vec<Opponent>,
+}
+
+impl GameState {
+    fn new() -> GameState {
+        GameState {
+            turn_count: 0,
+            our_score: 0,
+            enemy_score: 0,
+            ghosts: Vec::new(),
+            busters: Vec::new(),
+            opponents: Vec::new(),
+            enemy_memory: Vec::new(),
+        }
+    }
+    fn ghost(&self, ghost_id: i32) -> Option<&Ghost> {
+        self.ghosts.iter().find(|ghost| ghost.entity_id == ghost_id)
+    }
+    fn buster(&self, buster_id: i32) -> Option<&Buster> {
+        self.busters
+            .iter()
+            .find(|buster| buster.entity_id == buster_id)
+    }
+    fn opponent(&self, asshole_id: i32) -> Option<&Opponent> {
+        self.opponents
+            .iter()
+            .find(|asshole| asshole.entity_id == asshole_id)
+    }
+    fn opponent_mut(&mut self, asshole_id: i32) -> Option<&mut Opponent> {
+        self.opponents
+            .iter_mut()
+            .find(|asshole| asshole.entity_id == asshole_id)
+    }
+}
+
+// one turn's order for a buster in the forward model, the same commands the referee takes
+#[derive(Debug, Clone)]
+enum SimAction {
+    Move(Coord),
+    Bust(i32),
+    Stun(i32),
+    Release,
+    Eject(Coord),
+    Wait,
+}
+
+impl SimAction {
+    fn to_command(&self, buster: &Buster) -> String {
+        match self {
+            SimAction::Move(coords) => format!("MOVE {}", coords.to_string()),
+            SimAction::Bust(ghost_id) => format!("BUST {}", ghost_id),
+            SimAction::Stun(asshole_id) => format!("STUN {}", asshole_id),
+            SimAction::Release => String::from("RELEASE"),
+            SimAction::Eject(coords) => format!("EJECT {}", coords.to_string()),
+            SimAction::Wait => format!("MOVE {}", buster.coords.to_string()),
+        }
+    }
+}
+
+// what a buster keeps doing for a whole rollout, turned into a SimAction every turn
+#[derive(Debug, Clone)]
+enum SearchMove {
+    MoveTo(Coord),
+    Bust(i32),
+    Stun(i32),
+    GoHome,
+    Eject(Coord),
+    Radar,
+}
+
+impl SearchMove {
+    fn from_command(command: &str) -> SearchMove {
+        // the FSM's pick for this turn, quip and all
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        let number = |index: usize| {
+            parts
+                .get(index)
+                .and_then(|part| part.parse::<i32>().ok())
+                .unwrap_or(0)
+        };
+        match parts.first() {
+            Some(&"BUST") => SearchMove::Bust(number(1)),
+            Some(&"STUN") => SearchMove::Stun(number(1)),
+            Some(&"RELEASE") => SearchMove::GoHome,
+            Some(&"EJECT") => SearchMove::Eject(Coord::new(number(1), number(2))),
+            Some(&"RADAR") => SearchMove::Radar,
+            _ => SearchMove::MoveTo(Coord::new(number(1), number(2))),
+        }
+    }
+    fn from_state(buster: &Buster, command: &str) -> SearchMove {
+        // a MOVE is only this turn's waypoint, what the FSM is after comes from the state so a
+        // rollout of its pick keeps going where the FSM would
+        let intent = match buster.state {
+            BusterState::MovingToHome | BusterState::Releasing if buster.has_ghost => {
+                Some(SearchMove::GoHome)
+            }
+            BusterState::MovingToGhost
+            | BusterState::Busting
+            | BusterState::MovingAwayFromGhost
+            | BusterState::Herding => buster.busting_target_id.map(SearchMove::Bust),
+            BusterState::MovingToAsshole | BusterState::Stunning => {
+                buster.stunning_target_id.map(SearchMove::Stun)
+            }
+            _ => None,
+        };
+        intent.unwrap_or_else(|| SearchMove::from_command(command))
+    }
+}
+
+impl GameState {
+    //forward model, same order as the referee: stuns, then moves and releases, then busts,
+    //then whatever wasn't trapped runs
+    fn simulate(
+        &mut self,
+        ours: &[SimAction],
+        theirs: &[SimAction],
+        home: &Coord,
+        enemy_home: &Coord,
+    ) {
+        let start_coords: Vec<Coord> = self
+            .busters
+            .iter()
+            .map(|buster| buster.coords.clone())
+            .chain(self.opponents.iter().map(|asshole| asshole.coords.clone()))
+            .collect();
+        let mut dropped = Vec::new();
+
+        // a stun lasts longer than we look ahead, so whoever gets zapped is out for the rollout
+        let mut our_victims = Vec::new();
+        let mut their_victims = Vec::new();
+        for (buster, action) in self.busters.iter_mut().zip(ours.iter()) {
+            if let SimAction::Stun(asshole_id) = action {
+                let target = self
+                    .opponents
+                    .iter()
+                    .position(|asshole| asshole.entity_id == *asshole_id);
+                if let Some(index) = target {
+                    if !buster.is_stunned
+                        && buster.can_stun()
+                        && buster
+                            .coords
+                            .is_in_radius(&self.opponents[index].coords, STUN_RADIUS)
+                    {
+                        buster.stun_timer = STUN_COOLDOWN;
+                        their_victims.push(index);
+                    }
+                }
+            }
+        }
+        for (asshole, action) in self.opponents.iter_mut().zip(theirs.iter()) {
+            if let SimAction::Stun(buster_id) = action {
+                let target = self
+                    .busters
+                    .iter()
+                    .position(|buster| buster.entity_id == *buster_id);
+                if let Some(index) = target {
+                    if !asshole.is_stunned
+                        && asshole.can_stun()
+                        && asshole
+                            .coords
+                            .is_in_radius(&self.busters[index].coords, STUN_RADIUS)
+                    {
+                        asshole.stun_timer = STUN_COOLDOWN;
+                        our_victims.push(index);
+                    }
+                }
+            }
+        }
+        for index in our_victims {
+            let buster = &mut self.busters[index];
+            buster.is_stunned = true;
+            if buster.has_ghost {
+                buster.has_ghost = false;
+                dropped.push((buster.carried_ghost_id, buster.coords.clone()));
+            }
+        }
+        for index in their_victims {
+            let asshole = &mut self.opponents[index];
+            asshole.is_stunned = true;
+            if asshole.has_ghost {
+                asshole.has_ghost = false;
+                dropped.push((asshole.carried_ghost_id, asshole.coords.clone()));
+            }
+        }
+
+        // moves and releases, a ghost goes wherever its carrier goes
+        for (buster, action) in self.busters.iter_mut().zip(ours.iter()) {
+            if buster.is_stunned {
+                continue;
+            }
+            match action {
+                SimAction::Move(target) => {
+                    buster.coords = buster
+                        .coords
+                        .step_towards(target, BUSTER_MOVE_DISTANCE)
+                        .clamped();
+                }
+                SimAction::Release if buster.has_ghost => {
+                    buster.has_ghost = false;
+                    if buster.coords.is_in_radius(home, BASE_RELEASE_RADIUS) {
+                        self.our_score += 1;
+                    } else {
+                        dropped.push((buster.carried_ghost_id, buster.coords.clone()));
+                    }
+                }
+                SimAction::Eject(target) if buster.has_ghost => {
+                    buster.has_ghost = false;
+                    let landing = buster
+                        .coords
+                        .step_towards(target, EJECT_THROW_DISTANCE)
+                        .clamped();
+                    dropped.push((buster.carried_ghost_id, landing));
+                }
+                _ => {}
+            }
+        }
+        for (asshole, action) in self.opponents.iter_mut().zip(theirs.iter()) {
+            if asshole.is_stunned {
+                continue;
+            }
+            match action {
+                SimAction::Move(target) => {
+                    asshole.coords = asshole
+                        .coords
+                        .step_towards(target, BUSTER_MOVE_DISTANCE)
+                        .clamped();
+                }
+                SimAction::Release if asshole.has_ghost => {
+                    asshole.has_ghost = false;
+                    if asshole.coords.is_in_radius(enemy_home, BASE_RELEASE_RADIUS) {
+                        self.enemy_score += 1;
+                    } else {
+                        dropped.push((asshole.carried_ghost_id, asshole.coords.clone()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        for (ghost_id, coords) in dropped.iter() {
+            self.ghosts.retain(|ghost| ghost.entity_id != *ghost_id);
+            self.ghosts
+                .push(Ghost::new(*ghost_id, coords.x, coords.y, 0, 0));
+        }
+
+        // busts, a ghost with no stamina left goes to whichever team has more on it
+        let in_ring = |coords: &Coord, ghost: &Ghost| {
+            let dist = coords.distance_to(&ghost.coords);
+            (INNER_BUSTING_RADIUS..=OUTER_BUSTING_RADIUS).contains(&dist)
+        };
+        let mut caught = Vec::new();
+        for ghost in self.ghosts.iter_mut() {
+            let ours_on_it: Vec<usize> = (0..self.busters.len())
+                .filter(|&index| {
+                    let buster = &self.busters[index];
+                    !buster.is_stunned
+                        && !buster.has_ghost
+                        && matches!(ours[index], SimAction::Bust(id) if id == ghost.entity_id)
+                        && in_ring(&buster.coords, ghost)
+                })
+                .collect();
+            let theirs_on_it: Vec<usize> = (0..self.opponents.len())
+                .filter(|&index| {
+                    let asshole = &self.opponents[index];
+                    !asshole.is_stunned
+                        && !asshole.has_ghost
+                        && matches!(theirs[index], SimAction::Bust(id) if id == ghost.entity_id)
+                        && in_ring(&asshole.coords, ghost)
+                })
+                .collect();
+            let trappers = (ours_on_it.len() + theirs_on_it.len()) as i32;
+            ghost.people_trapping = trappers;
+            ghost.enemy_trapping = theirs_on_it.len() as i32;
+            if trappers == 0 {
+                continue;
+            }
+            ghost.stamina = (ghost.stamina - trappers).max(0);
+            if ghost.stamina > 0 || ours_on_it.len() == theirs_on_it.len() {
+                continue;
+            }
+            let ghost_coords = ghost.coords.clone();
+            if ours_on_it.len() > theirs_on_it.len() {
+                let catcher = ours_on_it
+                    .into_iter()
+                    .min_by_key(|&index| self.busters[index].coords.distance_to(&ghost_coords))
+                    .unwrap();
+                caught.push((ghost.entity_id, true, catcher));
+            } else {
+                let catcher = theirs_on_it
+                    .into_iter()
+                    .min_by_key(|&index| self.opponents[index].coords.distance_to(&ghost_coords))
+                    .unwrap();
+                caught.push((ghost.entity_id, false, catcher));
+            }
+        }
+        for (ghost_id, by_us, catcher) in caught {
+            if by_us {
+                self.busters[catcher].has_ghost = true;
+                self.busters[catcher].carried_ghost_id = ghost_id;
+            } else {
+                self.opponents[catcher].has_ghost = true;
+                self.opponents[catcher].carried_ghost_id = ghost_id;
+            }
+            self.ghosts.retain(|ghost| ghost.entity_id != ghost_id);
+        }
+
+        // whatever is loose and wasn't just let go of runs from the closest buster
+        for ghost in self.ghosts.iter_mut() {
+            if dropped
+                .iter()
+                .any(|(ghost_id, _)| *ghost_id == ghost.entity_id)
+            {
+                continue;
+            }
+            ghost.coords = ghost.predict_flee(&start_coords);
+        }
+
+        for buster in self.busters.iter_mut() {
+            buster.tick();
+        }
+        for asshole in self.opponents.iter_mut() {
+            asshole.tick();
+        }
+        self.turn_count += 1;
+    }
+
+    fn order_action(&self, buster: &Buster, order: &SearchMove, home: &Coord) -> SimAction {
+        // a carrier only ever heads home, whatever it was sent out for
+        let go_home = || {
+            if !buster.has_ghost {
+                SimAction::Wait
+            } else if buster.coords.is_in_radius(home, BASE_RELEASE_RADIUS) {
+                SimAction::Release
+            } else {
+                SimAction::Move(home.clone())
+            }
+        };
+        match order {
+            SearchMove::MoveTo(coords) => SimAction::Move(coords.clone()),
+            SearchMove::Bust(ghost_id) => match self.ghost(*ghost_id) {
+                _ if buster.has_ghost => go_home(),
+                Some(ghost) if ghost.is_bustable(buster) => SimAction::Bust(*ghost_id),
+                Some(ghost) => SimAction::Move(ghost.coords.step_towards(
+                    &buster.coords,
+                    (INNER_BUSTING_RADIUS + OUTER_BUSTING_RADIUS) / 2,
+                )),
+                None => SimAction::Wait,
+            },
+            SearchMove::Stun(asshole_id) => match self.opponent(*asshole_id) {
+                Some(asshole) if !asshole.is_stunned => {
+                    if buster.can_stun() && asshole.is_stunnable(buster) {
+                        SimAction::Stun(*asshole_id)
+                    } else {
+                        SimAction::Move(asshole.coords.clone())
+                    }
+                }
+                _ => SimAction::Wait,
+            },
+            SearchMove::GoHome => go_home(),
+            SearchMove::Eject(coords) if buster.has_ghost => SimAction::Eject(coords.clone()),
+            SearchMove::Eject(_) | SearchMove::Radar => SimAction::Wait,
+        }
+    }
+
+    fn their_actions(&self, enemy_home: &Coord) -> Vec<SimAction> {
+        // a plain guess at them: carriers go home, armed ones zap whoever they can reach,
+        // the rest bust or walk up to the closest ghost
+        self.opponents
+            .iter()
+            .map(|asshole| {
+                if asshole.is_stunned {
+                    return SimAction::Wait;
+                }
+                if asshole.has_ghost {
+                    return if asshole.coords.is_in_radius(enemy_home, BASE_RELEASE_RADIUS) {
+                        SimAction::Release
+                    } else {
+                        SimAction::Move(enemy_home.clone())
+                    };
+                }
+                if asshole.can_stun() {
+                    let victim = self
+                        .busters
+                        .iter()
+                        .filter(|buster| {
+                            !buster.is_stunned
+                                && buster.coords.is_in_radius(&asshole.coords, STUN_RADIUS)
+                        })
+                        .min_by_key(|buster| {
+                            (
+                                !buster.has_ghost,
+                                buster.coords.distance_to(&asshole.coords),
+                            )
+                        });
+                    if let Some(victim) = victim {
+                        return SimAction::Stun(victim.entity_id);
+                    }
+                }
+                let ghost = self
+                    .ghosts
+                    .iter()
+                    .filter(|ghost| {
+                        ghost.visible
+                            && ghost
+                                .coords
+                                .is_in_radius(&asshole.coords, LINE_OF_SIGHT_RADIUS)
+                    })
+                    .min_by_key(|ghost| ghost.coords.distance_to(&asshole.coords));
+                match ghost {
+                    Some(ghost) => {
+                        let dist = ghost.coords.distance_to(&asshole.coords);
+                        if (INNER_BUSTING_RADIUS..=OUTER_BUSTING_RADIUS).contains(&dist) {
+                            SimAction::Bust(ghost.entity_id)
+                        } else {
+                            SimAction::Move(ghost.coords.clone())
+                        }
+                    }
+                    None => SimAction::Move(Coord::new(
+                        asshole.coords.x + asshole.heading.x,
+                        asshole.coords.y + asshole.heading.y,
+                    )),
+                }
+            })
+            .collect()
+    }
+
+    fn evaluate(&self, home: &Coord, enemy_home: &Coord) -> i32 {
+        // points first, then ghosts on the way home, then who is still standing
+        let mut value = SEARCH_SCORE_WEIGHT * (self.our_score - self.enemy_score);
+        for buster in self.busters.iter() {
+            if buster.has_ghost {
+                value +=
+                    SEARCH_CARRY_WEIGHT - buster.coords.distance_to(home) / SEARCH_DISTANCE_DIVISOR;
+            }
+            if buster.is_stunned {
+                value -= SEARCH_STUNNED_WEIGHT;
+            } else if buster.can_stun() {
+                value += SEARCH_STUN_READY_WEIGHT;
+            }
+        }
+        for asshole in self.opponents.iter() {
+            if asshole.has_ghost {
+                value -= SEARCH_CARRY_WEIGHT
+                    - asshole.coords.distance_to(enemy_home) / SEARCH_DISTANCE_DIVISOR;
+            }
+            if asshole.is_stunned {
+                value += SEARCH_STUNNED_WEIGHT;
+            }
+        }
+        // a ghost we're winning the bust on counts for the share of it that's done, getting near
+        // one in the first place is the FSM's business
+        for ghost in self.ghosts.iter() {
+            let ours = ghost.people_trapping - ghost.enemy_trapping;
+            if ours > ghost.enemy_trapping {
+                value += SEARCH_CARRY_WEIGHT * ours / (ghost.stamina + ours);
+            }
+        }
+        value
+    }
+}
+
+struct StateMachine {
+    params: StrategyParams,
+    rng: StdRng,
+    fsm_trace: FsmTrace,
+    upper_left_home: bool,
+    state: GameState,
+    team_plan: Vec<PlanEntry>,
+    exploration: ExplorationGrid,
+    return_lanes: LaneMap,
+    ghost_count: i32,
+    seen_ghost_ids: Vec<i32>,
+    recaptures: Vec<i32>,
+    handoffs: Vec<(i32, i32)>, // ghost id, the teammate it was thrown to
+    strategy: Strategy,
+}
+
+impl StateMachine {
+    //other functions
+    fn lane_angle(&self, coords: &Coord) -> f64 {
+        // angle off their base's first wall, same for every spot on a straight run home
+        let enemy_home = self.enemy_home_coords();
+        let dx = (coords.x - enemy_home.x).abs() as f64;
+        let dy = (coords.y - enemy_home.y).abs() as f64;
+        dy.atan2(dx)
+    }
+    fn lane_camp_coords(&self, count: usize) -> Option<Vec<Coord>> {
+        let enemy_home = self.enemy_home_coords();
+        let (sign_x, sign_y) = if self.upper_left_home {
+            (-1.0, -1.0)
+        } else {
+            (1.0, 1.0)
+        };
+        self.return_lanes
+            .hottest_angles(count, self.params.lane_min_sightings)
+            .map(|angles| {
+                angles
+                    .iter()
+                    .map(|angle| {
+                        Coord::new(
+                            enemy_home.x
+                                + (sign_x * angle.cos() * self.params.camping_radius as f64) as i32,
+                            enemy_home.y
+                                + (sign_y * angle.sin() * self.params.camping_radius as f64) as i32,
+                        )
+                    })
+                    .collect()
+            })
+    }
+    fn throw_and_assign_coords(&mut self, is_camping: bool) -> () {
+        let num_busters = self.state.busters.len();
+        let origin = if self.upper_left_home != is_camping {
+            Coord::new(ORIGIN_THROWING_OFFSET, ORIGIN_THROWING_OFFSET)
+        } else {
+            Coord::new(
+                MAP_RIGHT_WALL - ORIGIN_THROWING_OFFSET,
+                MAP_BOTTOM_WALL - ORIGIN_THROWING_OFFSET,
+            )
+        };
+        // once we've seen enough of them coming home, sit where they actually walk
+        let lane_coords = if is_camping {
+            self.lane_camp_coords(num_busters)
+        } else {
+            None
+        };
+        let mut thrown_coords = lane_coords.unwrap_or_else(|| {
+            origin.throw_coords(
+                num_busters,
+                if is_camping {
+                    self.upper_left_home
+                } else {
+                    !self.upper_left_home
+                },
+                if is_camping {
+                    self.params.camping_radius
+                } else {
+                    self.params.capture_radius
+                },
+                if is_camping {
+                    self.params.camping_margin
+                } else {
+                    self.params.throw_margin
+                },
+            )
+        });
+
+        for buster in self.state.busters.iter_mut() {
+            let mut best_index = None;
+            let mut min_dist = i32::MAX;
+            for (index, coord) in thrown_coords.iter().enumerate() {
+                let dist = buster.coords.distance_to(coord);
+                if dist < min_dist {
+                    best_index = Some(index);
+                    min_dist = dist;
+                }
+            }
+
+            if let Some(index) = best_index {
+                let best_coord = thrown_coords.remove(index);
+                buster.movement_target.set(&best_coord);
+            }
+        }
+    }
+    fn set_new_buster_movement_coords(&mut self, buster: &mut Buster) -> () {
+        // spread out over what nobody has seen yet
+        let teammates: Vec<Coord> = self
+            .state
+            .busters
+            .iter()
+            .filter(|other| other.entity_id != buster.entity_id)
+            .flat_map(|other| {
+                let mut coords = vec![other.coords.clone()];
+                if matches!(other.state, BusterState::Searching) {
+                    coords.push(other.movement_target.clone());
+                }
+                coords
+            })
+            .collect();
+        let hints: Vec<Coord> = self
+            .state
+            .ghosts
+            .iter()
+            .filter(|ghost| ghost.probable)
+            .map(|ghost| ghost.coords.clone())
+            .collect();
+        if let Some(frontier) =
+            self.exploration
+                .best_frontier(&buster.coords, &teammates, &hints, &self.params)
+        {
+            buster.movement_target = frontier;
+            return;
+        }
+        // whole map seen, lol its just a rng
+        let new_coords = Coord::new(
+            self.rng.gen_range(0..MAP_RIGHT_WALL),
+            self.rng.gen_range(0..MAP_BOTTOM_WALL),
+        );
+        buster.movement_target = new_coords;
+    }
+    fn set_asshole_target(&mut self, asshole_id: i32, buster: &mut Buster) -> () {
+        if let Some(asshole) = self.state.opponent_mut(asshole_id) {
+            asshole.reserved_by = Some(buster.entity_id);
+        }
+        buster.stunning_target_id = Some(asshole_id);
+        if let Some(_) = &buster.busting_target_id {
+            buster.busting_target_id = None;
+        }
+    }
+    fn busting_target(&self, buster: &Buster) -> Option<&Ghost> {
+        buster.busting_target_id.and_then(|id| self.state.ghost(id))
+    }
+    fn stunning_target(&self, buster: &Buster) -> Option<&Opponent> {
+        buster
+            .stunning_target_id
+            .and_then(|id| self.state.opponent(id))
+    }
+    fn escorted_carrier(&self, buster: &Buster) -> Option<&Buster> {
+        buster.escorting_id.and_then(|id| self.state.buster(id))
+    }
+    fn planned_ghost(&self, buster: &Buster) -> Option<i32> {
+        self.team_plan
+            .iter()
+            .find(|entry| entry.buster_id == buster.entity_id)
+            .and_then(|entry| entry.ghost)
+    }
+    fn planned_asshole(&self, buster: &Buster) -> Option<i32> {
+        self.team_plan
+            .iter()
+            .find(|entry| entry.buster_id == buster.entity_id)
+            .and_then(|entry| entry.asshole)
+    }
+    fn planned_escort(&self, buster: &Buster) -> Option<i32> {
+        self.team_plan
+            .iter()
+            .find(|entry| entry.buster_id == buster.entity_id)
+            .and_then(|entry| entry.escort)
+    }
+    fn is_planned_escort(&self, buster: &Buster) -> bool {
+        match (self.planned_escort(buster), buster.escorting_id) {
+            (Some(planned), Some(current)) => planned == current,
+            _ => false,
+        }
+    }
+    fn recent_threats(&self) -> Vec<&Opponent> {
+        // opponents that could still be lurking where we last saw them
+        self.state
+            .enemy_memory
+            .iter()
+            .filter(|asshole| {
+                !asshole.is_stunned
+                    && asshole.last_seen_turn
+                        >= self.state.turn_count - self.params.escort_memory_turns
+            })
+            .collect()
+    }
+    fn escort_stun_target(&self, buster: &Buster) -> Option<i32> {
+        if !buster.can_stun() {
+            return None;
+        }
+        self.state
+            .opponents
+            .iter()
+            .find(|asshole| {
+                !asshole.is_stunned
+                    && asshole.is_stunnable(buster)
+                    && !asshole.is_reserved_for_other(buster)
+            })
+            .map(|asshole| asshole.entity_id)
+    }
+    fn escort_detour_over(&self, buster: &Buster) -> bool {
+        // an escort that peeled off for a stun goes back once it's thrown or the target's gone,
+        // Escorting itself sorts out whether the carrier still wants us
+        buster.escorting_id.is_some()
+            && (buster.state == BusterState::Stunning
+                || self
+                    .stunning_target(buster)
+                    .is_none_or(|asshole| asshole.is_reserved_for_other(buster)))
+    }
+    fn herd_spot(&self, ghost: &Ghost) -> Coord {
+        // right behind where the ghost will be, so it runs straight at our corner
+        let home = self.home_coords();
+        let target = &ghost.predicted_coords;
+        let behind = Coord::new(2 * target.x - home.x, 2 * target.y - home.y);
+        target
+            .step_towards(&behind, self.params.herding_distance)
+            .clamped()
+    }
+    fn herding_pays(&self, buster: &Buster, ghost: &Ghost) -> bool {
+        // only worth it while we're already behind it, nobody else can mess with it,
+        // and it still has a way to go
+        if !ghost.visible || ghost.probable || ghost.people_trapping > 0 {
+            return false;
+        }
+        let home = self.home_coords();
+        let herders = self
+            .team_plan
+            .iter()
+            .filter(|entry| entry.ghost == Some(ghost.entity_id))
+            .count();
+        let assholes_near = self.state.opponents.iter().any(|asshole| {
+            asshole
+                .coords
+                .is_in_radius(&ghost.coords, LINE_OF_SIGHT_RADIUS)
+        });
+        // walls eat the push, so it has to actually come closer
+        let push = self.herd_spot(ghost);
+        let flee_to = ghost.predicted_coords.step_towards(
+            &Coord::new(
+                2 * ghost.predicted_coords.x - push.x,
+                2 * ghost.predicted_coords.y - push.y,
+            ),
+            GHOST_FLEE_DISTANCE,
+        );
+        let progress =
+            ghost.predicted_coords.distance_to(&home) - flee_to.clamped().distance_to(&home);
+        herders == 1
+            && !assholes_near
+            && progress >= self.params.herding_min_progress
+            && !ghost
+                .predicted_coords
+                .is_in_radius(&home, self.params.herding_done_distance)
+            && buster.coords.is_in_radius(&push, self.params.herding_reach)
+    }
+    fn is_planned_ghost(&self, buster: &Buster) -> bool {
+        // current target is still what the team plan wants from this buster
+        match (self.planned_ghost(buster), buster.busting_target_id) {
+            (Some(planned), Some(current)) => planned == current,
+            _ => false,
+        }
+    }
+    fn radar_test(&self, buster: &Buster) -> bool {
+        // one shot per buster, so only spend it far from home, over lots of fog,
+        // with ghosts still unaccounted for and away from where a teammate already looked
+        let home = self.home_coords();
+        let unknown_ghosts = self.ghost_count > self.seen_ghost_ids.len() as i32;
+        let teammate_radar_nearby = self.state.busters.iter().any(|other| {
+            other.entity_id != buster.entity_id
+                && other.radar_turn >= 0
+                && other
+                    .radar_coords
+                    .is_in_radius(&buster.coords, RADAR_RADIUS)
+        });
+        buster.has_radar
+            && unknown_ghosts
+            && !teammate_radar_nearby
+            && !buster
+                .coords
+                .is_in_radius(&home, self.params.radar_min_base_distance)
+            && self.exploration.unseen_gain(&buster.coords, RADAR_RADIUS)
+                >= self.params.radar_min_unseen_cells
+    }
+    fn route_threats(&self) -> Vec<&Opponent> {
+        // only the ones we saw lately and that should have their stun back
+        self.recent_threats()
+            .into_iter()
+            .filter(|asshole| {
+                asshole.can_stun()
+                    && asshole.last_seen_turn
+                        >= self.state.turn_count - self.params.route_memory_turns
+            })
+            .collect()
+    }
+    fn route_clearance(
+        &self,
+        threats: &[&Opponent],
+        waypoint: &Coord,
+        ring_point: &Coord,
+    ) -> (i32, i32) {
+        // how close the next step and the rest of the way get to anyone who can stun
+        threats
+            .iter()
+            .fold((i32::MAX, i32::MAX), |(step, rest), asshole| {
+                (
+                    step.min(asshole.coords.distance_to(waypoint)),
+                    rest.min(asshole.coords.distance_to_segment(waypoint, ring_point)),
+                )
+            })
+    }
+    fn plan_route(&self, buster: &Buster) -> (Coord, bool) {
+        // next step home and whether it keeps us out of stun range
+        let home = self.home_coords();
+        let threats = self.route_threats();
+        let direct = buster.coords.step_towards(&home, BUSTER_MOVE_DISTANCE);
+        if threats.is_empty() {
+            return (direct, true);
+        }
+        let home_dist = buster.coords.distance_to(&home);
+        let mut candidates = vec![direct.clone()];
+        for heading in 0..ROUTE_HEADINGS {
+            let angle = heading as f64 / ROUTE_HEADINGS as f64 * 2.0 * std::f64::consts::PI;
+            let waypoint = Coord::new(
+                buster.coords.x + (angle.cos() * BUSTER_MOVE_DISTANCE as f64) as i32,
+                buster.coords.y + (angle.sin() * BUSTER_MOVE_DISTANCE as f64) as i32,
+            )
+            .clamped();
+            // a detour still has to get us closer, running in circles doesn't score
+            if waypoint.distance_to(&home) < home_dist {
+                candidates.push(waypoint);
+            }
+        }
+        // they get to move before they stun us, so stay out of their reach next turn
+        let reach = STUN_RADIUS + BUSTER_MOVE_DISTANCE;
+        let scored: Vec<(i32, i32, Coord)> = candidates
+            .into_iter()
+            .map(|waypoint| {
+                let ring_point = home.step_towards(&waypoint, BASE_RELEASE_RADIUS - 1);
+                let (step, rest) = self.route_clearance(&threats, &waypoint, &ring_point);
+                let length =
+                    buster.coords.distance_to(&waypoint) + waypoint.distance_to(&ring_point);
+                let danger = (STUN_RADIUS - rest).max(0) * self.params.route_danger_weight;
+                (length + danger, step, waypoint)
+            })
+            .collect();
+        if let Some((_, _, waypoint)) = scored
+            .iter()
+            .filter(|(_, step, _)| *step > reach)
+            .min_by_key(|(score, _, _)| *score)
+        {
+            return (waypoint.clone(), true);
+        }
+        // everything is in reach, at least keep as far from them as we can
+        let (_, _, waypoint) = scored.into_iter().max_by_key(|(_, step, _)| *step).unwrap();
+        (waypoint, false)
+    }
+    fn escort_coming(&self, buster: &Buster) -> bool {
+        self.state.busters.iter().any(|other| {
+            other.entity_id != buster.entity_id
+                && matches!(other.state, BusterState::Escorting)
+                && other.escorting_id == Some(buster.entity_id)
+        })
+    }
+    fn eject_catcher(&self, buster: &Buster) -> Option<&Buster> {
+        let home = self.home_coords();
+        let home_dist = buster.coords.distance_to(&home);
+        self.state
+            .busters
+            .iter()
+            .filter(|other| {
+                let dist = buster.coords.distance_to(&other.coords);
+                other.entity_id != buster.entity_id
+                    && !other.has_ghost
+                    && !other.is_stunned
+                    && other.coords.distance_to(&home) + self.params.eject_handoff_gain < home_dist
+                    && dist > EJECT_HANDOFF_CATCH_DISTANCE + INNER_BUSTING_RADIUS / 2
+                    && dist <= EJECT_THROW_DISTANCE + EJECT_HANDOFF_CATCH_DISTANCE
+            })
+            .min_by_key(|other| other.coords.distance_to(&home))
+    }
+    fn plan_eject(&self, buster: &Buster) -> Option<(BusterState, Coord)> {
+        let home = self.home_coords();
+        let home_dist = buster.coords.distance_to(&home);
+
+        // hand off to a free teammate that is a lot closer to home, landing in their busting ring
+        let catcher = self.eject_catcher(buster).map(|other| other.coords.clone());
+        if let Some(catcher_coords) = catcher {
+            let dist = buster.coords.distance_to(&catcher_coords);
+            let factor = (dist - EJECT_HANDOFF_CATCH_DISTANCE) as f64 / dist as f64;
+            return Some((
+                BusterState::EjectingToTeammate,
+                buster.coords.interpolate(&catcher_coords, factor),
+            ));
+        }
+
+        // no point throwing it if we can walk around them or help is on the way
+        if self.plan_route(buster).1 || self.escort_coming(buster) {
+            return None;
+        }
+        let threat = self
+            .state
+            .opponents
+            .iter()
+            .filter(|asshole| {
+                !asshole.is_stunned
+                    && asshole.can_stun()
+                    && asshole
+                        .coords
+                        .is_in_radius(&buster.coords, EJECT_THREAT_RADIUS)
+            })
+            .min_by_key(|asshole| asshole.coords.distance_to(&buster.coords))
+            .map(|asshole| asshole.coords.clone());
+        if let Some(threat_coords) = threat {
+            let factor = (EJECT_THROW_DISTANCE as f64 / home_dist.max(1) as f64).min(1.0);
+            let toward_home = buster.coords.interpolate(&home, factor);
+            if threat_coords.distance_to(&toward_home) >= buster.coords.distance_to(&toward_home) {
+                return Some((BusterState::EjectingToHome, toward_home));
+            }
+            // they're between us and home, throw it the other way if a teammate can grab it first
+            let threat_dist = buster.coords.distance_to(&threat_coords).max(1) as f64;
+            let away = Coord::new(
+                buster.coords.x
+                    + ((buster.coords.x - threat_coords.x) as f64 / threat_dist
+                        * EJECT_THROW_DISTANCE as f64) as i32,
+                buster.coords.y
+                    + ((buster.coords.y - threat_coords.y) as f64 / threat_dist
+                        * EJECT_THROW_DISTANCE as f64) as i32,
+            )
+            .clamped();
+            let recoverable = self.state.busters.iter().any(|other| {
+                other.entity_id != buster.entity_id
+                    && !other.has_ghost
+                    && !other.is_stunned
+                    && other.coords.distance_to(&away) < threat_coords.distance_to(&away)
+            });
+            if recoverable {
+                return Some((BusterState::EjectingAwayFromAsshole, away));
+            }
+        }
+        None
+    }
+    fn busting_spot(&self, buster: &Buster, ghost: &Ghost) -> Coord {
+        // fewest turns into the ring around where the ghost will be, home side breaks ties
+        let target = &ghost.predicted_coords;
+        let home = self.home_coords();
+        // a loose ghost runs from us once we get there, so stop short by that much
+        let flee = if ghost.visible && ghost.people_trapping == 0 {
+            GHOST_FLEE_DISTANCE
+        } else {
+            0
+        };
+        // the inside of the ring stays put though, if it doesn't run we'd be too close to bust
+        let inner = INNER_BUSTING_RADIUS + self.params.busting_ring_margin.max(1);
+        let outer = (OUTER_BUSTING_RADIUS - self.params.busting_ring_margin - flee).max(inner);
+        let dist = buster.coords.distance_to(target);
+        if dist >= inner && dist <= outer {
+            return buster.coords.clone();
+        }
+        let mut candidates = Vec::new();
+        if dist > 0 {
+            // straight in, or straight back out when we're on top of it
+            let radius = if dist > outer { outer } else { inner };
+            candidates.push(target.interpolate(&buster.coords, radius as f64 / dist as f64));
+        }
+        for sample in 0..BUSTING_RING_SAMPLES {
+            let angle = sample as f64 / BUSTING_RING_SAMPLES as f64 * 2.0 * std::f64::consts::PI;
+            for radius in [inner, outer] {
+                candidates.push(Coord::new(
+                    target.x + (angle.cos() * radius as f64) as i32,
+                    target.y + (angle.sin() * radius as f64) as i32,
+                ));
+            }
+        }
+        candidates
+            .into_iter()
+            .filter(|spot| {
+                spot.eq(&spot.clamped()) && !spot.is_in_radius(target, INNER_BUSTING_RADIUS)
+            })
+            .min_by_key(|spot| {
+                let dist = buster.coords.distance_to(spot);
+                let turns = (dist + BUSTER_MOVE_DISTANCE - 1) / BUSTER_MOVE_DISTANCE;
+                // the ghost will have moved by the time we get far, only pick a side up close
+                (
+                    turns,
+                    if turns <= 1 {
+                        spot.distance_to(&home)
+                    } else {
+                        dist
+                    },
+                )
+            })
+            .unwrap_or(target.clone())
+    }
+    fn home_coords(&self) -> Coord {
+        if self.upper_left_home {
+            Coord::new(0, 0)
+        } else {
+            Coord::new(MAP_RIGHT_WALL, MAP_BOTTOM_WALL)
+        }
+    }
+    fn enemy_home_coords(&self) -> Coord {
+        if self.upper_left_home {
+            Coord::new(MAP_RIGHT_WALL, MAP_BOTTOM_WALL)
+        } else {
+            Coord::new(0, 0)
+        }
+    }
+    fn target_explored(&self, buster: &Buster) -> bool {
+        // someone else already looked there, only matters while there's still fog left
+        self.exploration.has_unseen() && self.exploration.is_seen(&buster.movement_target)
+    }
+    fn ghost_difficulty_test(&self, ghost: &Ghost) -> bool {
+        if ghost.probable
+            && ghost.coords.distance_to(&self.enemy_home_coords())
+                < ghost.coords.distance_to(&self.home_coords())
+        {
+            // a guess on their side of the map is most likely in their trap already
+            return false;
+        }
+        return match ghost.stamina {
+            x if x > self.params.hard_ghost_stamina => {
+                self.state.turn_count >= self.params.turns_until_global_picks
+            }
+            _ => true,
+        };
+    }
+}
+
+fn ghost_travel_turns(coords: &Coord, ghost: &Ghost, uncertainty_turns: i32) -> i32 {
+    // turns until the ghost is inside busting range, remembered ghosts might have wandered
+    let dist = coords.distance_to(&ghost.predicted_coords);
+    let travel =
+        ((dist - OUTER_BUSTING_RADIUS).max(0) + BUSTER_MOVE_DISTANCE - 1) / BUSTER_MOVE_DISTANCE;
+    if ghost.visible {
+        travel
+    } else {
+        travel + uncertainty_turns
+    }
+}
+
+impl StateMachine {
+    //constructors and updators
+    fn new(
+        team_id: i32,
+        ghost_count: i32,
+        params: StrategyParams,
+        seed: u64,
+        fsm_trace: FsmTrace,
+    ) -> StateMachine {
+        StateMachine {
+            params,
+            rng: StdRng::seed_from_u64(seed),
+            fsm_trace,
+            upper_left_home: team_id == 0,
+            state: GameState::new(),
+            team_plan: Vec::new(),
+            exploration: ExplorationGrid::new(),
+            return_lanes: LaneMap::new(),
+            ghost_count,
+            seen_ghost_ids: Vec::new(),
+            recaptures: Vec::new(),
+            handoffs: Vec::new(),
+            strategy: Strategy::Normal,
+        }
+    }
+
+    fn update_ghosts(&mut self, new_ghosts: Vec<Ghost>) {
+        // everything we knew about goes into memory, the visible ones get refreshed below
+        for old_ghost in self.state.ghosts.iter_mut() {
+            old_ghost.visible = false;
+        }
+
+        for mut new_ghost in new_ghosts.into_iter() {
+            new_ghost.last_seen_turn = self.state.turn_count;
+            let ghost_id = new_ghost.entity_id;
+            let first_sighting = !self.seen_ghost_ids.contains(&ghost_id);
+            if first_sighting {
+                self.seen_ghost_ids.push(ghost_id);
+            }
+            if let Some(old_ghost) = self
+                .state
+                .ghosts
+                .iter_mut()
+                .find(|old_ghost| new_ghost.eq(old_ghost))
+            {
+                old_ghost.transfer_ghost_data(&new_ghost);
+            } else if let Some(probable) = self.state.ghosts.iter_mut().find(|old_ghost| {
+                old_ghost.probable
+                    && old_ghost
+                        .coords
+                        .is_in_radius(&new_ghost.coords, PROBABLE_MATCH_RADIUS)
+            }) {
+                // called it, move whoever is headed there over to the real id so they keep going
+                let probable_id = probable.entity_id;
+                probable.entity_id = ghost_id;
+                probable.probable = false;
+                probable.transfer_ghost_data(&new_ghost);
+                self.rename_ghost(probable_id, ghost_id);
+            } else {
+                self.state.ghosts.push(new_ghost.clone());
+            }
+            if first_sighting {
+                self.guess_mirrored_ghost(&new_ghost);
+            }
+        }
+
+        self.forget_ghosts();
+    }
+
+    fn rename_ghost(&mut self, old_id: i32, new_id: i32) {
+        for buster in self.state.busters.iter_mut() {
+            if buster.busting_target_id == Some(old_id) {
+                buster.busting_target_id = Some(new_id);
+            }
+        }
+        for entry in self.team_plan.iter_mut() {
+            if entry.ghost == Some(old_id) {
+                entry.ghost = Some(new_id);
+            }
+        }
+    }
+
+    fn guess_mirrored_ghost(&mut self, ghost: &Ghost) {
+        // the middle ghost is its own mirror, and there's no need to guess what we already know
+        let mirrored = ghost.coords.mirrored();
+        let already_known = mirrored.is_in_radius(&ghost.coords, PROBABLE_MATCH_RADIUS)
+            || self
+                .state
+                .ghosts
+                .iter()
+                .any(|known| known.coords.is_in_radius(&mirrored, PROBABLE_MATCH_RADIUS));
+        if !already_known {
+            self.state.ghosts.push(Ghost::new_probable(ghost));
+        }
+    }
+
+    fn forget_ghosts(&mut self) {
+        // a remembered ghost is dropped once it is captured or we look at the spot and it's gone
+        let mut to_remove = Vec::new();
+        for (index, ghost) in self.state.ghosts.iter().enumerate() {
+            if ghost.visible {
+                continue;
+            }
+            let revisited = self.state.busters.iter().any(|buster| {
+                buster
+                    .coords
+                    .is_in_radius(&ghost.coords, self.params.memory_revisit_radius)
+            });
+            let captured =
+                self.state.busters.iter().any(|buster| {
+                    buster.has_ghost
+                        && (buster.carried_ghost_id == ghost.entity_id
+                            || buster.busting_target_id == Some(ghost.entity_id))
+                }) || self.state.opponents.iter().any(|asshole| {
+                    asshole.has_ghost && asshole.carried_ghost_id == ghost.entity_id
+                });
+            if revisited || captured {
+                to_remove.push(index);
+            }
+        }
+
+        for index in to_remove.into_iter().rev() {
+            let ghost_id = self.state.ghosts[index].entity_id;
+
+            // Check if this ghost is being targeted by any good_buster
+            for buster in self.state.busters.iter_mut() {
+                if buster.busting_target_id == Some(ghost_id) {
+                    buster.busting_target_id = None;
+                }
+            }
+            eprintln!("forgetting ghost {}", ghost_id);
+            self.state.ghosts.remove(index);
+        }
+    }
+
+    fn update_exploration(&mut self) {
+        for buster in self.state.busters.iter() {
+            // a radar fired last turn shows up in this turn's input
+            let radius = if buster.radar_turn == self.state.turn_count {
+                RADAR_RADIUS
+            } else {
+                LINE_OF_SIGHT_RADIUS
+            };
+            self.exploration
+                .mark_seen(&buster.coords, radius, self.state.turn_count);
+        }
+    }
+
+    fn predict_ghosts(&mut self) {
+        let buster_coords: Vec<Coord> = self
+            .state
+            .busters
+            .iter()
+            .map(|buster| buster.coords.clone())
+            .chain(
+                self.state
+                    .opponents
+                    .iter()
+                    .map(|asshole| asshole.coords.clone()),
+            )
+            .collect();
+        for ghost in self.state.ghosts.iter_mut() {
+            ghost.predicted_coords = ghost.predict_flee(&buster_coords);
+        }
+    }
+
+    fn count_our_releases(&self, new_busters: &[Buster]) -> i32 {
+        // a release that went through leaves us empty handed and still standing
+        self.state
+            .busters
+            .iter()
+            .filter(|old_buster| {
+                matches!(old_buster.state, BusterState::Releasing)
+                    && old_buster.has_ghost
+                    && new_busters.iter().any(|new_buster| {
+                        new_buster.eq(old_buster) && !new_buster.has_ghost && !new_buster.is_stunned
+                    })
+            })
+            .count() as i32
+    }
+
+    fn count_enemy_releases(&mut self) {
+        // a carrier that had time to get home and shows up empty, or drops out of sight at
+        // their base, has scored
+        let enemy_home = self.enemy_home_coords();
+        let last_turn = self.state.turn_count - 1;
+        for known in self.state.enemy_memory.iter_mut() {
+            if !known.has_ghost {
+                continue;
+            }
+            let trip = known.coords.distance_to(&enemy_home) - BASE_RELEASE_RADIUS;
+            let released = match self
+                .state
+                .opponents
+                .iter()
+                .find(|asshole| known.eq(asshole))
+            {
+                Some(asshole) => {
+                    !asshole.has_ghost
+                        && !asshole.is_stunned
+                        && (self.state.turn_count - known.last_seen_turn) * BUSTER_MOVE_DISTANCE
+                            >= trip
+                }
+                None => known.last_seen_turn == last_turn && trip <= BUSTER_MOVE_DISTANCE,
+            };
+            if released {
+                self.state.enemy_score += 1;
+                known.has_ghost = false;
+            }
+        }
+    }
+
+    fn update_strategy(&mut self) {
+        let ours_carried = self
+            .state
+            .busters
+            .iter()
+            .filter(|buster| buster.has_ghost)
+            .count() as i32;
+        let theirs_carried = self
+            .state
+            .enemy_memory
+            .iter()
+            .filter(|known| known.has_ghost)
+            .count() as i32;
+        let left_for_us =
+            self.ghost_count - self.state.our_score - self.state.enemy_score - theirs_carried;
+        self.strategy = if self.state.our_score + ours_carried > self.ghost_count / 2 {
+            Strategy::Turtle
+        } else if self.state.our_score + left_for_us <= self.state.enemy_score + theirs_carried {
+            Strategy::Raid
+        } else {
+            Strategy::Normal
+        };
+        if self.fsm_trace.is_on() {
+            eprintln!(
+                "score: {}-{} strategy: {:?}",
+                self.state.our_score, self.state.enemy_score, self.strategy
+            );
+        }
+    }
+
+    fn camping_start_turn(&self) -> i32 {
+        // few ghosts left to find means waiting for theirs pays more than looking
+        let ghosts_left = self.ghost_count - self.state.our_score - self.state.enemy_score;
+        if ghosts_left <= self.params.camping_few_ghosts_left {
+            return 0;
+        }
+        self.params.start_camping_turn
+            + if self.state.our_score > self.state.enemy_score {
+                self.params.camping_score_shift
+            } else if self.state.our_score < self.state.enemy_score {
+                -self.params.camping_score_shift
+            } else {
+                0
+            }
+    }
+
+    fn record_lanes(&mut self) {
+        for asshole in self.state.opponents.iter() {
+            if asshole.has_ghost {
+                let angle = self.lane_angle(&asshole.coords);
+                self.return_lanes.record(angle);
+            }
+        }
+    }
+
+    fn camping_time(&self) -> bool {
+        match self.strategy {
+            Strategy::Normal => self.state.turn_count >= self.camping_start_turn(),
+            Strategy::Turtle => false,
+            Strategy::Raid => true,
+        }
+    }
+
+    fn newly_stunned(&self, new_busters: &[Buster]) -> Vec<Coord> {
+        // where our busters stood when they got zapped, stuns happen before anyone moves
+        self.state
+            .busters
+            .iter()
+            .filter(|old_buster| {
+                !old_buster.is_stunned
+                    && new_busters
+                        .iter()
+                        .any(|new_buster| new_buster.eq(old_buster) && new_buster.is_stunned)
+            })
+            .map(|old_buster| old_buster.coords.clone())
+            .collect()
+    }
+
+    fn track_enemies(&mut self, stunned_at: Vec<Coord>) {
+        for known in self.state.enemy_memory.iter_mut() {
+            known.tick();
+        }
+        // blame whoever was armed and in range last turn for each of our stunned busters
+        for victim_coords in stunned_at.iter() {
+            let last_turn = self.state.turn_count - 1;
+            if let Some(culprit) = self
+                .state
+                .enemy_memory
+                .iter_mut()
+                .filter(|known| {
+                    known.last_seen_turn == last_turn
+                        && !known.is_stunned
+                        && known.can_stun()
+                        && known
+                            .coords
+                            .is_in_radius(victim_coords, STUN_RADIUS + INTENT_STILL_DISTANCE)
+                })
+                .min_by_key(|known| known.coords.distance_to(victim_coords))
+            {
+                culprit.stun_timer = STUN_COOLDOWN;
+            }
+        }
+        let home = self.home_coords();
+        for asshole in self.state.opponents.iter_mut() {
+            let known = self
+                .state
+                .enemy_memory
+                .iter()
+                .find(|known| known.eq(&&*asshole));
+            asshole.heading = if let Some(known) = known {
+                let gap = (self.state.turn_count - known.last_seen_turn).max(1);
+                Coord::new(
+                    (asshole.coords.x - known.coords.x) / gap,
+                    (asshole.coords.y - known.coords.y) / gap,
+                )
+            } else {
+                Coord::new(0, 0)
+            };
+            asshole.stun_timer = known.map_or(0, |known| known.stun_timer);
+            let moved = asshole.heading.distance_to(&Coord::new(0, 0));
+            let closing_in = known.is_some_and(|known| {
+                self.state.busters.iter().any(|buster| {
+                    buster
+                        .coords
+                        .is_in_radius(&asshole.coords, LINE_OF_SIGHT_RADIUS)
+                        && known.coords.distance_to(&buster.coords)
+                            - asshole.coords.distance_to(&buster.coords)
+                            >= INTENT_CLOSING_DISTANCE
+                })
+            });
+            let on_ghost = self.state.ghosts.iter().any(|ghost| {
+                ghost.people_trapping > 0
+                    && ghost
+                        .coords
+                        .is_in_radius(&asshole.coords, OUTER_BUSTING_RADIUS)
+            });
+            asshole.intent = if asshole.has_ghost {
+                Intent::Returning
+            } else if known.is_none() {
+                Intent::Unknown
+            } else if moved <= INTENT_STILL_DISTANCE && on_ghost {
+                Intent::Busting
+            } else if closing_in {
+                Intent::Hunting
+            } else if moved <= INTENT_STILL_DISTANCE
+                && asshole
+                    .coords
+                    .is_in_radius(&home, self.params.camping_radius * 2)
+            {
+                Intent::Camping
+            } else {
+                Intent::Wandering
+            };
+        }
+    }
+
+    fn remember_enemies(&mut self) {
+        // last known whereabouts of every opponent, including ones radar showed us
+        for asshole in self.state.opponents.iter() {
+            let mut seen = asshole.clone();
+            seen.last_seen_turn = self.state.turn_count;
+            seen.reserved_by = None;
+            if let Some(known) = self
+                .state
+                .enemy_memory
+                .iter_mut()
+                .find(|known| known.eq(&&seen))
+            {
+                *known = seen;
+            } else {
+                self.state.enemy_memory.push(seen);
+            }
+        }
+    }
+
+    fn count_trappers(&mut self) {
+        // people_trapping counts both teams, whoever of ours isn't busting it must be them
+        for ghost in self.state.ghosts.iter_mut() {
+            let ours = self
+                .state
+                .busters
+                .iter()
+                .filter(|buster| {
+                    matches!(buster.state, BusterState::Busting)
+                        && buster.busting_target_id == Some(ghost.entity_id)
+                })
+                .count() as i32;
+            if ghost.visible {
+                ghost.enemy_trapping = (ghost.people_trapping - ours).max(0);
+            }
+        }
+    }
+
+    fn dropped_ghost_ids(
+        &self,
+        new_good: &[Buster],
+        new_evil: &[Opponent],
+    ) -> Vec<(i32, Option<i32>)> {
+        // a carrier that got zapped lets go of its ghost right where it stands, one that threw
+        // it left it lying wherever it landed, maybe with a teammate waiting to catch it
+        let mut dropped = Vec::new();
+        for old_buster in self.state.busters.iter() {
+            let new_buster = match new_good
+                .iter()
+                .find(|&new_buster| new_buster.eq(old_buster))
+            {
+                Some(new_buster) => new_buster,
+                None => continue,
+            };
+            if !old_buster.has_ghost || old_buster.is_stunned || new_buster.has_ghost {
+                continue;
+            }
+            let thrown = matches!(
+                old_buster.state,
+                BusterState::EjectingToTeammate
+                    | BusterState::EjectingToHome
+                    | BusterState::EjectingAwayFromAsshole
+            );
+            if new_buster.is_stunned {
+                dropped.push((old_buster.carried_ghost_id, None));
+            } else if thrown {
+                dropped.push((old_buster.carried_ghost_id, old_buster.eject_catcher_id));
+            }
+        }
+        let enemy_home = self.enemy_home_coords();
+        for old_asshole in self.state.opponents.iter() {
+            let dropped_it = new_evil.iter().any(|new_asshole| {
+                new_asshole.eq(old_asshole)
+                    && !new_asshole.has_ghost
+                    && (new_asshole.is_stunned
+                        || !new_asshole
+                            .coords
+                            .is_in_radius(&enemy_home, BASE_RELEASE_RADIUS))
+            });
+            if old_asshole.has_ghost && !old_asshole.is_stunned && dropped_it {
+                dropped.push((old_asshole.carried_ghost_id, None));
+            }
+        }
+        dropped
+    }
+
+    fn track_recaptures(&mut self, dropped: Vec<(i32, Option<i32>)>) {
+        for (ghost_id, catcher_id) in dropped.into_iter() {
+            if ghost_id >= 0 && !self.recaptures.contains(&ghost_id) {
+                eprintln!("ghost {} dropped, going back for it", ghost_id);
+                self.recaptures.push(ghost_id);
+            }
+            if let Some(catcher_id) = catcher_id {
+                self.handoffs
+                    .retain(|(handed_id, _)| *handed_id != ghost_id);
+                self.handoffs.push((ghost_id, catcher_id));
+            }
+        }
+        // done once someone's carrying it or it's been forgotten
+        let all_ghosts = &self.state.ghosts;
+        self.recaptures
+            .retain(|ghost_id| all_ghosts.iter().any(|ghost| ghost.entity_id == *ghost_id));
+        let recaptures = &self.recaptures;
+        self.handoffs
+            .retain(|(ghost_id, _)| recaptures.contains(ghost_id));
+    }
+
+    fn assign_recaptures(&mut self) {
+        // a dropped ghost is a free point for whoever is closer, so it beats the plan
+        let mut taken: Vec<i32> = Vec::new();
+        for ghost_id in self.recaptures.iter() {
+            let ghost_coords = match self.state.ghost(*ghost_id) {
+                Some(ghost) => ghost.coords.clone(),
+                None => continue,
+            };
+            let free: Vec<&Buster> = self
+                .state
+                .busters
+                .iter()
+                .filter(|buster| {
+                    !buster.has_ghost && !buster.is_stunned && !taken.contains(&buster.entity_id)
+                })
+                .collect();
+            // a thrown ghost goes to the teammate it was thrown to, if they're still free
+            let catcher = self
+                .handoffs
+                .iter()
+                .find(|(handed_id, _)| handed_id == ghost_id)
+                .and_then(|(_, catcher_id)| {
+                    free.iter().find(|buster| buster.entity_id == *catcher_id)
+                })
+                .map(|buster| buster.entity_id);
+            let nearest = catcher.or_else(|| {
+                free.iter()
+                    .min_by_key(|buster| buster.coords.distance_to(&ghost_coords))
+                    .map(|buster| buster.entity_id)
+            });
+            if let Some(buster_id) = nearest {
+                taken.push(buster_id);
+                // busy busters were left out of the plan, they get pulled in for this
+                self.team_plan.retain(|entry| entry.buster_id != buster_id);
+                self.team_plan.push(PlanEntry {
+                    buster_id,
+                    ghost: Some(*ghost_id),
+                    asshole: None,
+                    escort: None,
+                });
+            }
+        }
+    }
+
+    fn resolve_contests(&mut self) {
+        // a tie at zero stamina goes to nobody, so a ghost they have at least as many on as we
+        // plan to either gets help from someone free and close or gets left to them
+        let mut called_in: Vec<i32> = Vec::new();
+        for index in 0..self.state.ghosts.len() {
+            let ghost = &self.state.ghosts[index];
+            let ghost_id = ghost.entity_id;
+            let ours = self
+                .team_plan
+                .iter()
+                .filter(|entry| entry.ghost == Some(ghost_id))
+                .count() as i32;
+            if !ghost.visible || ours == 0 || ours > ghost.enemy_trapping {
+                continue;
+            }
+            let mut helpers: Vec<(i32, i32)> = self
+                .state
+                .busters
+                .iter()
+                .filter(|buster| {
+                    !buster.has_ghost
+                        && !buster.is_stunned
+                        && !called_in.contains(&buster.entity_id)
+                        && self.team_plan.iter().any(|entry| {
+                            entry.buster_id == buster.entity_id
+                                && entry.ghost.is_none()
+                                && entry.asshole.is_none()
+                                && entry.escort.is_none()
+                        })
+                })
+                .map(|buster| (self.ghost_travel_turns(buster, index), buster.entity_id))
+                .filter(|(turns, _)| *turns <= self.params.contest_reinforce_turns)
+                .collect();
+            helpers.sort();
+            let needed = (ghost.enemy_trapping + 1 - ours) as usize;
+            if helpers.len() >= needed {
+                for (_, buster_id) in helpers.into_iter().take(needed) {
+                    called_in.push(buster_id);
+                    if let Some(entry) = self
+                        .team_plan
+                        .iter_mut()
+                        .find(|entry| entry.buster_id == buster_id)
+                    {
+                        entry.ghost = Some(ghost_id);
+                    }
+                }
+            } else {
+                for entry in self.team_plan.iter_mut() {
+                    if entry.ghost == Some(ghost_id) {
+                        entry.ghost = None;
+                    }
+                }
+            }
+        }
+    }
+
+    fn reserve_planned_targets(&mut self) {
+        // last turn's holds are gone, whoever the plan sends gets first go this turn
+        for asshole in self.state.opponents.iter_mut() {
+            asshole.reserved_by = None;
+        }
+        for entry in self.team_plan.iter() {
+            if let Some(asshole) = entry.asshole.and_then(|id| self.state.opponent_mut(id)) {
+                asshole.reserved_by = Some(entry.buster_id);
+            }
+        }
+    }
+
+    fn plan_assignments(&mut self) {
+        // one joint plan per turn: every free buster gets a ghost, a stun target or nothing,
+        // whichever combination costs the fewest buster-turns for the rewards it brings in
+        let home = self.home_coords();
+        let escort_threats: Vec<i32> = self
+            .state
+            .busters
+            .iter()
+            .map(|carrier| {
+                if !carrier.has_ghost
+                    || carrier.is_stunned
+                    || carrier
+                        .coords
+                        .is_in_radius(&home, BASE_RELEASE_RADIUS + BUSTER_MOVE_DISTANCE)
+                {
+                    return 0;
+                }
+                let threats = self
+                    .recent_threats()
+                    .iter()
+                    .filter(|asshole| {
+                        asshole.coords.distance_to_segment(&carrier.coords, &home)
+                            <= self.params.escort_threat_radius
+                    })
+                    .count() as i32;
+                // when it's the winning ghost nobody goes home alone
+                if self.strategy == Strategy::Turtle {
+                    threats.max(1)
+                } else {
+                    threats
+                }
+            })
+            .collect();
+
+        let mut plan_busters = Vec::new();
+        for buster in self.state.busters.iter() {
+            let busy = buster.has_ghost
+                || buster.is_stunned
+                || matches!(
+                    buster.state,
+                    BusterState::MovingToHome
+                        | BusterState::Releasing
+                        | BusterState::UsingRadar
+                        | BusterState::EjectingToTeammate
+                        | BusterState::EjectingToHome
+                        | BusterState::EjectingAwayFromAsshole
+                );
+            if busy {
+                continue;
+            }
+            let is_camper = matches!(
+                buster.state,
+                BusterState::Camping | BusterState::MovingToCamp
+            );
+
+            let mut ghost_options: Vec<usize> = (0..self.state.ghosts.len())
+                .filter(|&index| self.ghost_difficulty_test(&self.state.ghosts[index]))
+                .collect();
+            ghost_options.sort_by_key(|&index| self.ghost_travel_turns(buster, index));
+            ghost_options.truncate(ASSIGNMENT_CANDIDATES);
+
+            let mut options = vec![Assignment::Nothing];
+            options.extend(ghost_options.into_iter().map(Assignment::Ghost));
+            if buster.can_stun() {
+                for (index, asshole) in self.state.opponents.iter().enumerate() {
+                    // campers are only there for carriers
+                    if !asshole.is_stunned
+                        && asshole
+                            .coords
+                            .is_in_radius(&buster.coords, LINE_OF_SIGHT_RADIUS)
+                        && (asshole.has_ghost || !is_camper)
+                    {
+                        options.push(Assignment::Asshole(index));
+                    }
+                }
+            }
+            for (index, threats) in escort_threats.iter().enumerate() {
+                if *threats > 0 {
+                    options.push(Assignment::Escort(index));
+                }
+            }
+            let current = self.current_assignment(buster);
+            let max_gain = options
+                .iter()
+                .map(|option| self.option_gain(option, is_camper, &current))
+                .max()
+                .unwrap_or(0);
+            plan_busters.push(PlanBuster {
+                entity_id: buster.entity_id,
+                coords: buster.coords.clone(),
+                is_camper,
+                can_stun: buster.can_stun(),
+                options,
+                current,
+                max_gain,
+            });
+        }
+
+        let mut chosen = vec![Assignment::Nothing; plan_busters.len()];
+        let mut best = chosen.clone();
+        let mut best_cost = i32::MAX;
+        self.search_assignments(
+            &plan_busters,
+            &escort_threats,
+            0,
+            &mut chosen,
+            &mut best,
+            &mut best_cost,
+        );
+
+        self.team_plan = plan_busters
+            .iter()
+            .zip(best.iter())
+            .map(|(plan_buster, assignment)| PlanEntry {
+                buster_id: plan_buster.entity_id,
+                ghost: match assignment {
+                    Assignment::Ghost(index) => Some(self.state.ghosts[*index].entity_id),
+                    _ => None,
+                },
+                asshole: match assignment {
+                    Assignment::Asshole(index) => Some(self.state.opponents[*index].entity_id),
+                    _ => None,
+                },
+                escort: match assignment {
+                    Assignment::Escort(index) => Some(self.state.busters[*index].entity_id),
+                    _ => None,
+                },
+            })
+            .collect();
+    }
+
+    fn current_assignment(&self, buster: &Buster) -> Assignment {
+        let ghost = buster.busting_target_id.and_then(|id| {
+            self.state
+                .ghosts
+                .iter()
+                .position(|ghost| ghost.entity_id == id)
+        });
+        let asshole = buster.stunning_target_id.and_then(|id| {
+            self.state
+                .opponents
+                .iter()
+                .position(|asshole| asshole.entity_id == id)
+        });
+        let escort = buster.escorting_id.and_then(|id| {
+            self.state
+                .busters
+                .iter()
+                .position(|other| other.entity_id == id)
+        });
+        match (ghost, asshole, escort) {
+            (Some(index), _, _) => Assignment::Ghost(index),
+            (_, Some(index), _) => Assignment::Asshole(index),
+            (_, _, Some(index)) => Assignment::Escort(index),
+            _ => Assignment::Nothing,
+        }
+    }
+
+    fn option_gain(&self, option: &Assignment, is_camper: bool, current: &Assignment) -> i32 {
+        // an upper bound on how much cheaper one buster taking this option can make the plan,
+        // a ghost's group sharing the bust can save up to one turn per buster already on it
+        let gain = match option {
+            Assignment::Nothing => return 0,
+            Assignment::Ghost(_) => {
+                self.params.assignment_ghost_reward.max(0)
+                    + self.params.coop_shortfall_penalty.max(0)
+                    + self.state.busters.len() as i32
+            }
+            Assignment::Asshole(index) => {
+                let asshole = &self.state.opponents[*index];
+                let reward = if asshole.has_ghost {
+                    self.params.assignment_stun_carrier_reward
+                } else {
+                    self.params.assignment_stun_reward
+                };
+                let armed = if asshole.is_dangerous() {
+                    self.params.assignment_stun_armed_reward
+                } else {
+                    0
+                };
+                reward.max(0) + armed.max(0)
+            }
+            Assignment::Escort(_) => self.params.escort_reward.max(0),
+        };
+        let gain = if option == current {
+            gain + self.params.assignment_commit_bonus.max(0)
+        } else {
+            gain
+        };
+        if is_camper {
+            gain - self.params.assignment_camper_penalty.min(0)
+        } else {
+            gain
+        }
+    }
+
+    fn search_assignments(
+        &self,
+        plan_busters: &[PlanBuster],
+        escort_threats: &[i32],
+        depth: usize,
+        chosen: &mut Vec<Assignment>,
+        best: &mut Vec<Assignment>,
+        best_cost: &mut i32,
+    ) {
+        // everyone past depth is still on Nothing, so this is the cost of the plan so far
+        let cost = self.assignment_cost(plan_busters, escort_threats, chosen);
+        if depth == plan_busters.len() {
+            if cost < *best_cost {
+                *best_cost = cost;
+                best.clone_from(chosen);
+            }
+            return;
+        }
+        // the rest can't win back enough to beat what we've got
+        let remaining_gain: i32 = plan_busters[depth..]
+            .iter()
+            .map(|plan_buster| plan_buster.max_gain)
+            .sum();
+        if cost - remaining_gain >= *best_cost {
+            return;
+        }
+        for option in plan_busters[depth].options.iter() {
+            // one stunner per target is plenty
+            if let Assignment::Asshole(_) = option {
+                if chosen[..depth].contains(option) {
+                    continue;
+                }
+            }
+            chosen[depth] = *option;
+            self.search_assignments(
+                plan_busters,
+                escort_threats,
+                depth + 1,
+                chosen,
+                best,
+                best_cost,
+            );
+        }
+        chosen[depth] = Assignment::Nothing;
+    }
+
+    fn assignment_cost(
+        &self,
+        plan_busters: &[PlanBuster],
+        escort_threats: &[i32],
+        chosen: &[Assignment],
+    ) -> i32 {
+        let mut cost = 0;
+        for (plan_buster, assignment) in plan_busters.iter().zip(chosen.iter()) {
+            if plan_buster.is_camper && *assignment != Assignment::Nothing {
+                cost += self.params.assignment_camper_penalty;
+            }
+            // flipping between two about as good targets every turn gets neither of them done
+            if *assignment != Assignment::Nothing && *assignment == plan_buster.current {
+                cost -= self.params.assignment_commit_bonus;
+            }
+            if let Assignment::Asshole(index) = assignment {
+                let asshole = &self.state.opponents[*index];
+                let dist = plan_buster.coords.distance_to(&asshole.coords);
+                let travel = (dist - STUN_RADIUS).max(0) / BUSTER_MOVE_DISTANCE;
+                cost += travel
+                    - if asshole.has_ghost {
+                        self.params.assignment_stun_carrier_reward
+                    } else {
+                        self.params.assignment_stun_reward
+                    };
+                // take their stun away before they use it on us
+                if asshole.is_dangerous() {
+                    cost -= self.params.assignment_stun_armed_reward;
+                }
+            }
+        }
+        // an escort per threat near the carrier's way home, extras just tag along
+        for (index, threats) in escort_threats.iter().enumerate() {
+            if *threats == 0 {
+                continue;
+            }
+            let carrier_coords = self.state.busters[index].coords.clone();
+            let mut escorts = 0;
+            for (plan_buster, assignment) in plan_busters.iter().zip(chosen.iter()) {
+                if *assignment == Assignment::Escort(index) {
+                    escorts += 1;
+                    cost += plan_buster.coords.distance_to(&carrier_coords) / BUSTER_MOVE_DISTANCE;
+                    if escorts <= *threats {
+                        cost -= self.params.escort_reward;
+                    }
+                }
+            }
+        }
+        // each ghost keeps all of its busters busy until it's caught
+        for (index, ghost) in self.state.ghosts.iter().enumerate() {
+            let assigned: Vec<&PlanBuster> = plan_busters
+                .iter()
+                .zip(chosen.iter())
+                .filter(|(_, assignment)| **assignment == Assignment::Ghost(index))
+                .map(|(plan_buster, _)| plan_buster)
+                .collect();
+            if assigned.is_empty() {
+                continue;
+            }
+            let count = assigned.len() as i32;
+            // someone out for us near the ghost and nothing to answer with
+            let ambushed = self.state.opponents.iter().any(|asshole| {
+                asshole.is_dangerous()
+                    && asshole
+                        .coords
+                        .is_in_radius(&ghost.coords, EJECT_THREAT_RADIUS)
+            });
+            if ambushed {
+                cost += self.params.assignment_danger_penalty
+                    * assigned
+                        .iter()
+                        .filter(|plan_buster| !plan_buster.can_stun)
+                        .count() as i32;
+            }
+            let travel = assigned
+                .iter()
+                .map(|plan_buster| {
+                    ghost_travel_turns(
+                        &plan_buster.coords,
+                        ghost,
+                        self.params.memory_uncertainty_turns,
+                    )
+                })
+                .max()
+                .unwrap();
+            // their busts drain stamina too, but a tie or fewer of us means they walk off with it
+            let trappers = count + ghost.enemy_trapping;
+            let bust_turns = (ghost.stamina.max(1) + trappers - 1) / trappers;
+            let reward = if count > ghost.enemy_trapping {
+                self.params.assignment_ghost_reward
+            } else {
+                0
+            };
+            let shortfall = (ghost.busters_needed(
+                plan_busters.len() as i32,
+                self.params.coop_target_bust_turns,
+            ) - count)
+                .max(0);
+            cost += count * (travel + bust_turns) + shortfall * self.params.coop_shortfall_penalty
+                - reward;
+        }
+        cost
+    }
+
+    fn ghost_travel_turns(&self, buster: &Buster, ghost_index: usize) -> i32 {
+        ghost_travel_turns(
+            &buster.coords,
+            &self.state.ghosts[ghost_index],
+            self.params.memory_uncertainty_turns,
+        )
+    }
+
+    fn update_good(&mut self, new_busters: Vec<Buster>) -> () {
+        for old_buster in self.state.busters.iter_mut() {
+            if let Some(new_buster) = new_busters
+                .iter()
+                .find(|&new_buster| new_buster.eq(old_buster))
+            {
+                old_buster.transfer_buster_data(new_buster);
+            }
+        }
+    }
+
+    fn update_evil(&mut self, new_busters: Vec<Opponent>) -> () {
+        // Indices of bad busters that need to be removed
+        let mut to_remove = Vec::new();
+
+        for (index, old_buster) in self.state.opponents.iter_mut().enumerate() {
+            if let Some(new_buster) = new_busters
+                .iter()
+                .find(|&new_buster| new_buster.eq(old_buster))
+            {
+                old_buster.transfer_opponent_data(new_buster);
+            } else {
+                to_remove.push(index);
+            }
+        }
+
+        for index in to_remove.into_iter().rev() {
+            let evil_id = self.state.opponents[index].entity_id;
+
+            // Check if this bad buster is being targeted by any good_buster
+            for good_buster in self.state.busters.iter_mut() {
+                if good_buster.stunning_target_id == Some(evil_id) {
+                    good_buster.stunning_target_id = None;
+                }
             }
 
-            self.bad_busters.remove(index);
+            self.state.opponents.remove(index);
         }
 
         // Append any new busters not present in bad_busters
-        for new_buster_rc in new_busters.iter() {
+        for new_buster in new_busters.into_iter() {
             if self
-                .bad_busters
+                .state
+                .opponents
                 .iter()
-                .find(|&old_buster_rc| old_buster_rc.borrow().eq(&new_buster_rc.borrow()))
+                .find(|&old_buster| old_buster.eq(&new_buster))
                 .is_none()
             {
-                self.bad_busters.push(new_buster_rc.clone());
+                self.state.opponents.push(new_buster);
             }
         }
     }
@@ -597,21 +3677,33 @@ impl StateMachine {
 
 impl StateMachine {
     //what to do for each state
-    fn do_state(&self, mut buster: std::cell::RefMut<'_, Buster>) -> String {
+    fn do_state(&mut self, buster: &mut Buster) -> String {
         let mut result = match &buster.state {
-            BusterState::Idle => unreachable!(),
             BusterState::Searching => self.do_searching(&buster),
             BusterState::Busting => self.do_bust(&buster),
             BusterState::MovingAwayFromGhost => self.do_away_ghost_move(&buster),
             BusterState::MovingToAsshole => self.do_to_asshole_move(&buster),
-            BusterState::Stunning => self.do_stun(&mut buster),
-            BusterState::MovingToHome => self.do_go_home(),
+            BusterState::Stunning => self.do_stun(buster),
+            BusterState::MovingToHome => self.do_go_home(buster),
             BusterState::MovingToGhost => self.do_to_ghost_move(&buster),
+            BusterState::Herding => self.do_herd_move(buster),
             BusterState::Releasing => self.do_release(),
+            BusterState::UsingRadar => self.do_radar(buster),
+            BusterState::EjectingToTeammate
+            | BusterState::EjectingToHome
+            | BusterState::EjectingAwayFromAsshole => self.do_eject(buster),
             BusterState::Stunned => self.do_searching(&buster), // placeholder
             BusterState::MovingToCamp => self.do_searching(&buster), //this works because moving is set
             BusterState::Camping => self.do_searching(&buster),
-            _ => unreachable!(),
+            BusterState::Escorting => self.do_escort_move(buster),
+            // nothing should be left sitting in these after a slide, keep walking if it is
+            BusterState::Idle => {
+                eprintln!(
+                    "fsm: {} has no action in {:?}",
+                    buster.entity_id, buster.state
+                );
+                self.do_searching(buster)
+            }
         };
         result.push_str(match &buster.state {
             BusterState::Idle => " Doing fuck all",
@@ -623,6 +3715,12 @@ impl StateMachine {
             BusterState::Stunning => " An electric bust",
             BusterState::MovingToGhost => " Bring me that ass",
             BusterState::Releasing => " Go to the pokeball",
+            BusterState::UsingRadar => " Ping",
+            BusterState::EjectingToTeammate => " Catch!",
+            BusterState::EjectingToHome => " Yeet",
+            BusterState::EjectingAwayFromAsshole => " Keep away",
+            BusterState::Escorting => " Got your back",
+            BusterState::Herding => " Yeehaw",
             //TODO make more funnies
             _ => "",
         });
@@ -631,76 +3729,109 @@ impl StateMachine {
     fn do_searching(&self, buster: &Buster) -> String {
         return format!("MOVE {}", buster.movement_target.to_string());
     }
+    fn do_fallback(&self, buster: &Buster, missing: &str) -> String {
+        // the slide can stop short of settling, keep walking rather than act on nothing
+        self.fsm_trace.note(&format!(
+            "{} turn {}: {:?} has no {}, searching instead",
+            buster.entity_id, self.state.turn_count, buster.state, missing
+        ));
+        self.do_searching(buster)
+    }
     fn do_bust(&self, buster: &Buster) -> String {
-        return format!(
-            "BUST {}",
-            buster
-                .busting_target_ref
-                .as_ref()
-                .unwrap()
-                .borrow()
-                .entity_id
-        );
+        let Some(ghost) = self.busting_target(buster) else {
+            return self.do_fallback(buster, "ghost");
+        };
+        return format!("BUST {}", ghost.entity_id);
     }
-    fn do_stun(&self, buster: &mut Buster) -> String {
+    fn do_stun(&mut self, buster: &mut Buster) -> String {
+        let asshole = match buster
+            .stunning_target_id
+            .and_then(|id| self.state.opponent_mut(id))
+        {
+            Some(asshole) => asshole,
+            None => return self.do_searching(buster),
+        };
+        if asshole.is_reserved_for_other(buster) {
+            // a teammate is zapping them this turn, stay close in case it misses
+            return format!("MOVE {}", asshole.coords.to_string());
+        }
+        asshole.reserved_by = Some(buster.entity_id);
         buster.stun_timer = STUN_COOLDOWN;
-        return format!(
-            "STUN {}",
-            buster
-                .stunning_target_ref
-                .as_ref()
-                .unwrap()
-                .borrow()
-                .entity_id
-        );
+        return format!("STUN {}", asshole.entity_id);
     }
     fn do_away_ghost_move(&self, buster: &Buster) -> String {
-        let ghost_coords = &buster.busting_target_ref.as_ref().unwrap().borrow().coords;
-
-        let dx = ghost_coords.x - buster.coords.x;
-        let dy = ghost_coords.y - buster.coords.y;
-        if buster.coords.distance_to(&Coord { x: dy, y: dx }) < 300 {
-            return format!("MOVE 0 0");
-        }
-        // Now calculate the reflection by subtracting the vector from the buster's position
-        let reflected_x = buster.coords.x - dx;
-        let reflected_y = buster.coords.y - dy;
-
-        format!("MOVE {} {}", reflected_x, reflected_y)
+        // back out to the ring, not all the way through the ghost
+        self.do_to_ghost_move(buster)
     }
     fn do_to_ghost_move(&self, buster: &Buster) -> String {
-        return format!(
-            "MOVE {}",
-            buster
-                .busting_target_ref
-                .as_ref()
-                .unwrap()
-                .borrow()
-                .coords
-                .to_string()
-        );
+        let Some(ghost) = self.busting_target(buster) else {
+            return self.do_fallback(buster, "ghost");
+        };
+        return format!("MOVE {}", self.busting_spot(buster, ghost).to_string());
+    }
+    fn do_herd_move(&self, buster: &Buster) -> String {
+        let Some(ghost) = self.busting_target(buster) else {
+            return self.do_fallback(buster, "ghost");
+        };
+        format!("MOVE {}", self.herd_spot(ghost).to_string())
     }
     fn do_to_asshole_move(&self, buster: &Buster) -> String {
+        let Some(asshole) = self.stunning_target(buster) else {
+            return self.do_fallback(buster, "asshole");
+        };
+        return format!("MOVE {}", asshole.coords.to_string());
+    }
+    fn do_escort_move(&self, buster: &Buster) -> String {
+        // stay next to where the carrier will be, on the side of whoever might jump it
+        let home = self.home_coords();
+        let Some(carrier) = self.escorted_carrier(buster) else {
+            return self.do_fallback(buster, "carrier");
+        };
+        let carrier_next = self.plan_route(carrier).0;
+        let threat = self
+            .recent_threats()
+            .into_iter()
+            .min_by_key(|asshole| asshole.coords.distance_to(&carrier_next));
+        let escort_coords = match threat {
+            Some(asshole) => carrier_next.step_towards(&asshole.coords, self.params.escort_offset),
+            None => carrier_next.step_towards(&home, self.params.escort_offset),
+        };
+        format!("MOVE {}", escort_coords.to_string())
+    }
+    fn do_go_home(&self, buster: &Buster) -> String {
+        let (waypoint, safe) = self.plan_route(buster);
+        if safe || !self.escort_coming(buster) {
+            return format!("MOVE {}", waypoint.to_string());
+        }
+        // every way home is covered, back off from them until the escort shows up
+        let threats = self.route_threats();
+        let Some(nearest) = threats
+            .iter()
+            .min_by_key(|asshole| asshole.coords.distance_to(&buster.coords))
+        else {
+            return format!("MOVE {}", waypoint.to_string());
+        };
+        let retreat = Coord::new(
+            2 * buster.coords.x - nearest.coords.x,
+            2 * buster.coords.y - nearest.coords.y,
+        );
         return format!(
             "MOVE {}",
             buster
-                .stunning_target_ref
-                .as_ref()
-                .unwrap()
-                .borrow()
                 .coords
+                .step_towards(&retreat, BUSTER_MOVE_DISTANCE)
+                .clamped()
                 .to_string()
         );
     }
-    fn do_go_home(&self) -> String {
-        return format!(
-            "MOVE {}",
-            if self.upper_left_home {
-                Coord::new(0, 0).to_string()
-            } else {
-                Coord::new(MAP_RIGHT_WALL, MAP_BOTTOM_WALL).to_string()
-            }
-        );
+    fn do_radar(&self, buster: &mut Buster) -> String {
+        buster.has_radar = false;
+        buster.radar_turn = self.state.turn_count;
+        buster.radar_coords = buster.coords.clone();
+        String::from("RADAR")
+    }
+    fn do_eject(&self, buster: &Buster) -> String {
+        format!("EJECT {}", buster.eject_target.to_string())
     }
     fn do_release(&self) -> String {
         // buster.state = BusterState::Searching;
@@ -710,164 +3841,160 @@ impl StateMachine {
 
 impl StateMachine {
     //state transitions and tests
-    fn state_slide(&self, mut buster_refmut: std::cell::RefMut<'_, Buster>) -> () {
-        while self.should_transition(&*buster_refmut) {
+    fn state_slide(&mut self, buster: &mut Buster) -> () {
+        let mut slide = SlideTrace::new(self.state.turn_count, buster.entity_id);
+        let mut settled = false;
+        for _ in 0..FSM_MAX_SLIDE_STEPS {
+            let from = buster.state;
+            let spec = match state_spec(from) {
+                Some(spec) => spec,
+                None => {
+                    eprintln!("fsm: no spec for {:?}, searching instead", from);
+                    buster.state = BusterState::Searching;
+                    continue;
+                }
+            };
+            if !(spec.exit)(self, buster) {
+                settled = true;
+                break;
+            }
+            (spec.prepare)(self, buster);
+            let rule = TRANSITION_RULES
+                .iter()
+                .find(|rule| rule.from == from && (rule.guard)(self, buster));
+            if let Some(rule) = rule {
+                (rule.action)(self, buster);
+                buster.state = rule.to;
+                slide.push(TraceStep {
+                    from,
+                    guard: rule.name,
+                    to: rule.to,
+                });
+            } else {
+                // nothing matched, take the fallback and stop sliding for this turn
+                eprintln!(
+                    "fsm: {} left {:?} on {} but no rule matched, falling back to {:?}",
+                    buster.entity_id, from, spec.exit_name, spec.fallback
+                );
+                buster.state = spec.fallback;
+                slide.push(TraceStep {
+                    from,
+                    guard: "fallback",
+                    to: spec.fallback,
+                });
+                settled = true;
+                break;
+            }
+        }
+        if !settled {
             eprintln!(
-                "{} transitioned from {:?}",
-                buster_refmut.entity_id, buster_refmut.state
+                "fsm: {} still sliding after {} steps, staying {:?}",
+                buster.entity_id, FSM_MAX_SLIDE_STEPS, buster.state
             );
-            self.do_transition(&mut *buster_refmut);
-        }
-    }
-
-    fn should_transition(&self, buster: &Buster) -> bool {
-        match buster.state {
-            BusterState::Idle => true,
-            BusterState::Searching => self.searching_test(buster),
-            BusterState::Busting => self.busting_test(buster),
-            BusterState::MovingToHome => self.house_move_test(buster),
-            BusterState::MovingAwayFromGhost => self.ghost_moving_away_test(buster),
-            BusterState::MovingToAsshole => self.asshole_moving_test(buster),
-            BusterState::Stunning => self.stunning_test(buster),
-            BusterState::MovingToGhost => self.ghost_moving_test(buster),
-            BusterState::Releasing => self.release_test(buster),
-            BusterState::Stunned => self.stunned_test(buster),
-            BusterState::Camping => self.camping_test(buster),
-            BusterState::MovingToCamp => self.camp_moving_test(buster),
-            _ => false,
         }
+        self.fsm_trace.record(&slide);
     }
+
     fn camping_test(&self, buster: &Buster) -> bool {
-        let stunnable_asshole_in_radius_with_ghost = self.bad_busters.iter().any(|asshole| {
-            asshole
-                .borrow()
-                .coords
-                .is_in_radius(&buster.coords, LINE_OF_SIGHT_RADIUS)
-                && !matches!(asshole.borrow().state, BusterState::DeathMarked)
-                && buster.can_stun()
-                && asshole.borrow().has_ghost
-        });
-        let ghost_in_radius = self.all_ghosts.iter().any(|ghost| {
-            ghost
-                .borrow()
-                .coords
-                .is_in_radius(&buster.coords, LINE_OF_SIGHT_RADIUS)
-        });
-        let asking_for_help = !self.global_ping_stack.is_empty();
-        stunnable_asshole_in_radius_with_ghost || ghost_in_radius || asking_for_help
+        self.planned_ghost(buster).is_some()
+            || self.planned_asshole(buster).is_some()
+            || self.planned_escort(buster).is_some()
     }
     fn camp_moving_test(&self, buster: &Buster) -> bool {
-        let ghost_in_radius = self.all_ghosts.iter().any(|ghost| {
-            ghost
-                .borrow()
-                .coords
-                .is_in_radius(&buster.coords, LINE_OF_SIGHT_RADIUS)
-        });
-        let stunnable_asshole_in_radius_with_ghost = self.bad_busters.iter().any(|asshole| {
-            asshole
-                .borrow()
-                .coords
-                .is_in_radius(&buster.coords, LINE_OF_SIGHT_RADIUS)
-                && !matches!(asshole.borrow().state, BusterState::DeathMarked)
-                && buster.can_stun()
-                && asshole.borrow().has_ghost
-        });
         let made_it_to_camp = buster.coords.eq(&buster.movement_target);
-        let asking_for_help = !self.global_ping_stack.is_empty();
-        stunnable_asshole_in_radius_with_ghost
-            || made_it_to_camp
-            || ghost_in_radius
-            || asking_for_help
+        made_it_to_camp
+            || self.planned_ghost(buster).is_some()
+            || self.planned_asshole(buster).is_some()
+            || self.planned_escort(buster).is_some()
+    }
+    fn escorting_test(&self, buster: &Buster) -> bool {
+        //carrier made it, plan changed or someone is asking for it
+        !self.is_planned_escort(buster) || self.escort_stun_target(buster).is_some()
+    }
+    fn herding_test(&self, buster: &Buster) -> bool {
+        //plan changed or pushing it further stopped paying off
+        self.planned_asshole(buster).is_some()
+            || !self.is_planned_ghost(buster)
+            || self
+                .busting_target(buster)
+                .is_none_or(|ghost| !self.herding_pays(buster, ghost))
     }
     fn stunned_test(&self, buster: &Buster) -> bool {
         !buster.is_stunned
     }
     fn searching_test(&self, buster: &Buster) -> bool {
-        let ghost_in_radius = self.all_ghosts.iter().any(|ghost| {
-            ghost
-                .borrow()
-                .coords
-                .is_in_radius(&buster.coords, LINE_OF_SIGHT_RADIUS)
-                && self.ghost_difficulty_test(&ghost.borrow())
-        });
-        let stunnable_asshole_in_radius = self.bad_busters.iter().any(|asshole| {
-            asshole
-                .borrow()
-                .coords
-                .is_in_radius(&buster.coords, LINE_OF_SIGHT_RADIUS)
-                && !matches!(asshole.borrow().state, BusterState::DeathMarked)
-                && buster.can_stun()
-                && !asshole.borrow().is_stunned
-        });
-        let camping_time = self.turn_count >= START_CAMPING_TURN;
+        let planned_ghost = self.planned_ghost(buster).is_some();
+        let planned_asshole = self.planned_asshole(buster).is_some();
+        let planned_escort = self.planned_escort(buster).is_some();
+        let camping_time = self.camping_time();
         let made_it_to_target = buster.coords.eq(&buster.movement_target);
-        let asking_for_help =
-            (!self.global_ping_stack.is_empty() && self.turn_count > TURNS_UNTIL_GLOBAL_PICKS);
+        let target_explored = self.target_explored(buster);
+        let radar_time = self.radar_test(buster);
 
-        stunnable_asshole_in_radius
-            || ghost_in_radius
+        planned_asshole
+            || planned_ghost
+            || planned_escort
             || made_it_to_target
-            || asking_for_help
+            || target_explored
+            || radar_time
             || camping_time
     }
     fn asshole_moving_test(&self, buster: &Buster) -> bool {
-        if let Some(asshole) = &buster.stunning_target_ref {
-            //target exists, determine if in range
-            asshole.borrow().is_stunnable(buster)
+        if let Some(asshole) = self.stunning_target(buster) {
+            //target exists, determine if in range or someone else has them
+            asshole.is_stunnable(buster) || asshole.is_reserved_for_other(buster)
         } else {
             //target no longer exists
             true
         }
     }
     fn ghost_moving_away_test(&self, buster: &Buster) -> bool {
-        if let Some(ghost) = &buster.busting_target_ref {
+        if !self.is_planned_ghost(buster) || self.planned_asshole(buster).is_some() {
+            //plan changed
+            true
+        } else if let Some(ghost) = self.busting_target(buster) {
             //target exists and range is determined
-            !ghost.borrow().is_too_close(buster)
+            !ghost.is_too_close(buster)
         } else {
             //target no longer exists
             true
         }
     }
     fn ghost_moving_test(&self, buster: &Buster) -> bool {
-        if let Some(_) = self.find_best_asshole_in_los(buster) {
+        if self.planned_asshole(buster).is_some() || !self.is_planned_ghost(buster) {
+            //plan changed
             true
-        } else if let Some(ghost) = &buster.busting_target_ref {
+        } else if let Some(ghost) = self.busting_target(buster) {
             //target exists and range is determined
-            if let Some(best_ghost) = self.find_best_ghost_in_los(buster) {
-                if best_ghost.ne(&ghost) && best_ghost.borrow().stamina <= ghost.borrow().stamina {
-                    return true;
-                }
-            }
             buster
                 .coords
-                .is_in_radius(&ghost.borrow().coords, OUTER_BUSTING_RADIUS) //encapsulate case that we are too close
+                .is_in_radius(&ghost.coords, OUTER_BUSTING_RADIUS) //encapsulate case that we are too close
+                || self.herding_pays(buster, ghost)
         } else {
             //target no longer exists
             true
         }
     }
     fn busting_test(&self, buster: &Buster) -> bool {
-        return if let Some(_) = self.find_best_asshole_in_los(buster) {
+        return if let Some(_) = self.planned_asshole(buster) {
             true
         } else {
-            buster.busting_target_ref.is_none()
+            buster.busting_target_id.is_none() || !self.is_planned_ghost(buster)
         };
     }
     fn stunning_test(&self, buster: &Buster) -> bool {
-        if let Some(asshole) = &buster.stunning_target_ref {
-            asshole.borrow().is_stunned
+        if let Some(asshole) = self.stunning_target(buster) {
+            asshole.is_stunned || asshole.is_reserved_for_other(buster)
         } else {
             !buster.can_stun()
         }
     }
     fn house_move_test(&self, buster: &Buster) -> bool {
-        let house_coords = if self.upper_left_home {
-            Coord::new(0, 0)
-        } else {
-            Coord::new(MAP_RIGHT_WALL, MAP_BOTTOM_WALL)
-        };
-        //at house or lost ghost
-        return house_coords.is_in_radius(&buster.coords, BASE_RELEASE_RADIUS) || !buster.has_ghost;
+        let house_coords = self.home_coords();
+        //at house, lost ghost or better off throwing it
+        return house_coords.is_in_radius(&buster.coords, BASE_RELEASE_RADIUS)
+            || !buster.has_ghost
+            || self.plan_eject(buster).is_some();
     }
     fn release_test(&self, buster: &Buster) -> bool {
         return !buster.has_ghost;
@@ -875,273 +4002,415 @@ impl StateMachine {
 }
 
 impl StateMachine {
-    //how to transition
-    fn do_transition(&self, buster: &mut Buster) -> () {
-        match buster.state {
-            BusterState::Idle => self.do_idle_transition(buster),
-            BusterState::Searching => self.do_searching_transition(buster),
-            BusterState::Busting => self.do_busting_transition(buster),
-            BusterState::MovingToHome => self.do_home_move_transition(buster),
-            BusterState::MovingAwayFromGhost => self.do_ghost_away_move_transition(buster),
-            BusterState::MovingToAsshole => self.do_asshole_move_transition(buster),
-            BusterState::Stunning => self.do_stunning_transition(buster),
-            BusterState::MovingToGhost => self.do_ghost_move_transition(buster),
-            BusterState::Releasing => self.do_release_transition(buster),
-            BusterState::Stunned => self.do_idle_transition(buster),
-            BusterState::MovingToCamp => self.do_camp_move_transition(buster),
-            BusterState::Camping => self.do_camping_transition(buster),
-            _ => unreachable!(),
-        }
-    }
-    fn do_camping_transition(&self, buster: &mut Buster) -> () {
-        buster.state = if let Some(ghost_in_radius) = self.all_ghosts.iter().find(|ghost| {
-            ghost
-                .borrow()
-                .coords
-                .is_in_radius(&buster.coords, LINE_OF_SIGHT_RADIUS)
-        }) {
-            buster.busting_target_ref = Some(Rc::clone(ghost_in_radius));
-            BusterState::MovingToGhost
-        } else if let Some(stunnable_asshole_in_radius_with_ghost) =
-            self.bad_busters.iter().find(|asshole| {
-                asshole
-                    .borrow()
-                    .coords
-                    .is_in_radius(&buster.coords, LINE_OF_SIGHT_RADIUS)
-                    && !matches!(asshole.borrow().state, BusterState::DeathMarked)
-                    && buster.can_stun()
-                    && asshole.borrow().has_ghost
-            })
-        {
-            self.set_asshole_target(stunnable_asshole_in_radius_with_ghost, buster);
-            BusterState::MovingToAsshole
-        } else if let Some(ghost_being_targetted) = self.steal_target_ghost_reference(buster) {
-            buster.busting_target_ref = Some(ghost_being_targetted);
-            BusterState::MovingToGhost
-        } else {
-            unreachable!()
+    //transition actions, the rules in TRANSITION_RULES pick which one runs
+    fn take_planned_ghost(&mut self, buster: &mut Buster) {
+        buster.busting_target_id = self.planned_ghost(buster);
+    }
+    fn take_planned_asshole(&mut self, buster: &mut Buster) {
+        if let Some(asshole) = self.planned_asshole(buster) {
+            self.set_asshole_target(asshole, buster);
         }
     }
-    fn do_camp_move_transition(&self, buster: &mut Buster) -> () {
-        buster.state = if buster.coords.eq(&buster.movement_target) {
-            BusterState::Camping
-        } else if let Some(ghost_in_radius) = self.all_ghosts.iter().find(|ghost| {
-            ghost
-                .borrow()
-                .coords
-                .is_in_radius(&buster.coords, LINE_OF_SIGHT_RADIUS)
-        }) {
-            buster.busting_target_ref = Some(Rc::clone(ghost_in_radius));
-            BusterState::MovingToGhost
-        } else if let Some(stunnable_asshole_in_radius_with_ghost) =
-            self.bad_busters.iter().find(|asshole| {
-                asshole
-                    .borrow()
-                    .coords
-                    .is_in_radius(&buster.coords, LINE_OF_SIGHT_RADIUS)
-                    && !matches!(asshole.borrow().state, BusterState::DeathMarked)
-                    && buster.can_stun()
-                    && asshole.borrow().has_ghost
-            })
-        {
-            self.set_asshole_target(stunnable_asshole_in_radius_with_ghost, buster);
-            BusterState::MovingToAsshole
-        } else if let Some(ghost_being_targetted) = self.steal_target_ghost_reference(buster) {
-            buster.busting_target_ref = Some(ghost_being_targetted);
-            BusterState::MovingToGhost
+    fn take_eject_target(&mut self, buster: &mut Buster) {
+        if let Some((state, eject_target)) = self.plan_eject(buster) {
+            buster.eject_target = eject_target;
+            // whoever it's thrown to should know to pick it up
+            buster.eject_catcher_id = match state {
+                BusterState::EjectingToTeammate => {
+                    self.eject_catcher(buster).map(|other| other.entity_id)
+                }
+                _ => None,
+            };
+        }
+    }
+    fn start_escort(&mut self, buster: &mut Buster) {
+        // remember what we were up to so we can pick it back up after the release
+        buster.escort_return_state = match buster.state {
+            BusterState::Camping | BusterState::MovingToCamp => BusterState::MovingToCamp,
+            _ => BusterState::Searching,
+        };
+        buster.escort_return_target = buster.movement_target.clone();
+        buster.escorting_id = self.planned_escort(buster);
+    }
+    fn end_escort(&mut self, buster: &mut Buster) {
+        buster.escorting_id = None;
+        buster.movement_target = buster.escort_return_target.clone();
+    }
+}
+
+impl StateMachine {
+    //ticks
+    fn update_tick(
+        &mut self,
+        new_ghosts: Vec<Ghost>,
+        new_good: Vec<Buster>,
+        new_evil: Vec<Opponent>,
+    ) -> () {
+        let stunned_at = self.newly_stunned(&new_good);
+        let dropped = self.dropped_ghost_ids(&new_good, &new_evil);
+        self.state.our_score += self.count_our_releases(&new_good);
+        if self.state.busters.is_empty() {
+            self.state.busters = new_good;
+            self.throw_and_assign_coords(false);
+            self.state.opponents = new_evil;
         } else {
-            unreachable!()
+            self.update_good(new_good);
+            self.update_evil(new_evil);
+        }
+        self.update_ghosts(new_ghosts);
+        self.track_recaptures(dropped);
+        self.count_enemy_releases();
+        self.track_enemies(stunned_at);
+        self.remember_enemies();
+        self.record_lanes();
+        self.update_exploration();
+        self.predict_ghosts();
+        self.count_trappers();
+        let was_camping = self.camping_time();
+        self.update_strategy();
+        self.plan_assignments();
+        self.assign_recaptures();
+        self.resolve_contests();
+        self.reserve_planned_targets();
+        self.state.turn_count += 1;
+        if self.camping_time()
+            && (!was_camping || self.state.turn_count % self.params.camping_replace_turns == 0)
+        {
+            self.throw_and_assign_coords(true);
         }
+        eprintln!("turn count: {}", self.state.turn_count);
     }
-    fn do_idle_transition(&self, buster: &mut Buster) -> () {
-        buster.state = BusterState::Searching;
+    fn player_tick(&mut self, player_ind: usize) -> String {
+        // the buster is taken out while it slides and written back after, so the rest of the
+        // state stays readable the whole time
+        let mut player = self.state.busters[player_ind].clone();
+        if player.is_stunned {
+            player.state = BusterState::Stunned
+        } else {
+            self.state_slide(&mut player);
+        }
+        player.tick();
+        let result = self.do_state(&mut player);
+        self.state.busters[player_ind] = player;
+        result
     }
+}
 
-    fn do_ghost_move_transition(&self, buster: &mut Buster) -> () {
-        if let Some(preferable_ghost) = self.find_best_ghost_in_los(buster) {
-            // Check if this new preferable ghost is different than the current target
-            if buster
-                .busting_target_ref
-                .as_ref()
-                .map_or(true, |current_target| {
-                    !Rc::ptr_eq(current_target, &preferable_ghost)
-                })
-            {
-                // If it's different, update the target to the new preferable ghost
-                buster.busting_target_ref = Some(Rc::clone(preferable_ghost));
-                return;
-            }
+impl StateMachine {
+    //forward search, runs over the FSM's picks and only keeps what it can show is better
+    fn search_moves(&self, buster: &Buster, fsm_command: &str) -> Vec<SearchMove> {
+        // the FSM's pick always comes first so it's what we fall back on
+        let mut moves = vec![SearchMove::from_state(buster, fsm_command)];
+        if buster.is_stunned || matches!(moves[0], SearchMove::Radar) {
+            return moves;
         }
-        buster.state = if let Some(asshole) = self.find_best_asshole_in_los(buster) {
-            self.set_asshole_target(&asshole, buster);
-            BusterState::MovingToAsshole
-        } else if let Some(ghost) = &buster.busting_target_ref {
-            //target exists and range is determined
-            // let best_ghost = self.find_best_ghost_in_LOS(buster).unwrap();
-            if ghost.borrow().is_too_close(buster) {
-                //too close to ghost
-                BusterState::MovingAwayFromGhost
-            } else {
-                //right distance
-                BusterState::Busting
+        let home = self.home_coords();
+        if buster.has_ghost {
+            moves.push(SearchMove::GoHome);
+            moves.push(SearchMove::Eject(
+                buster.coords.step_towards(&home, EJECT_THROW_DISTANCE),
+            ));
+            return moves;
+        }
+        let mut ghosts: Vec<&Ghost> = self
+            .state
+            .ghosts
+            .iter()
+            .filter(|ghost| !ghost.probable)
+            .collect();
+        ghosts.sort_by_key(|ghost| ghost.coords.distance_to(&buster.coords));
+        for ghost in ghosts.into_iter().take(SEARCH_GHOST_CANDIDATES) {
+            moves.push(SearchMove::Bust(ghost.entity_id));
+        }
+        if buster.can_stun() {
+            for asshole in self.state.opponents.iter() {
+                if !asshole.is_stunned
+                    && asshole
+                        .coords
+                        .is_in_radius(&buster.coords, LINE_OF_SIGHT_RADIUS)
+                {
+                    moves.push(SearchMove::Stun(asshole.entity_id));
+                }
             }
-        } else {
-            //target no longer exists
-            BusterState::Searching
         }
+        moves.push(SearchMove::MoveTo(buster.movement_target.clone()));
+        moves
     }
-    fn do_asshole_move_transition(&self, buster: &mut Buster) -> () {
-        buster.state = if let Some(asshole) = &buster.stunning_target_ref {
-            BusterState::Stunning
-        } else {
-            BusterState::Searching
+
+    fn rollout(&self, start: &GameState, orders: &[SearchMove]) -> i32 {
+        let home = self.home_coords();
+        let enemy_home = self.enemy_home_coords();
+        let mut sim = start.clone();
+        for _ in 0..self.params.search_depth {
+            let ours = sim
+                .busters
+                .iter()
+                .zip(orders.iter())
+                .map(|(buster, order)| sim.order_action(buster, order, &home))
+                .collect::<Vec<SimAction>>();
+            let theirs = sim.their_actions(&enemy_home);
+            sim.simulate(&ours, &theirs, &home, &enemy_home);
         }
+        sim.evaluate(&home, &enemy_home)
     }
-    fn do_ghost_away_move_transition(&self, buster: &mut Buster) -> () {
-        if let Some(preferable_ghost) = self.find_best_ghost_in_los(buster) {
-            // Check if this new preferable ghost is different than the current target
-            if buster
-                .busting_target_ref
-                .as_ref()
-                .map_or(true, |current_target| {
-                    !Rc::ptr_eq(current_target, &preferable_ghost)
-                })
-            {
-                // If it's different, update the target to the new preferable ghost
-                buster.busting_target_ref = Some(preferable_ghost.clone());
+
+    fn search_turn(&mut self, fsm_commands: Vec<String>, turn_start: Instant) -> Vec<String> {
+        // the deadline is the one thing a replay can't pin down, a turn takes about a millisecond
+        // at the default sizes so it never gets close
+        let deadline =
+            turn_start + Duration::from_millis(self.params.search_budget_ms.max(0) as u64);
+        // the FSM already paid for its stuns, whoever it sent to zap can still do it in here
+        let mut start = self.state.clone();
+        start.ghosts.retain(|ghost| !ghost.probable);
+        for (buster, command) in start.busters.iter_mut().zip(fsm_commands.iter()) {
+            if command.starts_with("STUN") {
+                buster.stun_timer = 0;
+            }
+        }
+        let candidates: Vec<Vec<SearchMove>> = start
+            .busters
+            .iter()
+            .zip(fsm_commands.iter())
+            .map(|(buster, command)| self.search_moves(buster, command))
+            .collect();
+        let as_orders = |picks: &[usize]| {
+            picks
+                .iter()
+                .enumerate()
+                .map(|(index, &pick)| candidates[index][pick].clone())
+                .collect::<Vec<SearchMove>>()
+        };
+
+        // beam over the busters one at a time, everyone not picked yet does what the FSM said
+        let fsm_picks = vec![0; candidates.len()];
+        let fsm_value = self.rollout(&start, &as_orders(&fsm_picks));
+        let mut best = (fsm_value, fsm_picks.clone());
+        let mut beam = vec![best.clone()];
+        let mut out_of_time = false;
+        for index in 0..candidates.len() {
+            let mut next = beam.clone();
+            for (parent_value, picks) in beam.iter() {
+                for pick in 1..candidates[index].len() {
+                    if Instant::now() >= deadline {
+                        out_of_time = true;
+                        break;
+                    }
+                    let mut tried = picks.clone();
+                    tried[index] = pick;
+                    let value = self.rollout(&start, &as_orders(&tried));
+                    // every override has to pay for itself, not ride along on someone else's
+                    if value < parent_value + self.params.search_override_margin {
+                        continue;
+                    }
+                    if value > best.0 {
+                        best = (value, tried.clone());
+                    }
+                    next.push((value, tried));
+                }
+            }
+            // stable sort, so on a tie whatever is closer to the FSM stays ahead
+            next.sort_by_key(|(value, _)| -value);
+            next.truncate(self.params.search_beam_width.max(1) as usize);
+            beam = next;
+            if out_of_time {
+                break;
+            }
+        }
+
+        let mut commands = fsm_commands;
+        for (index, &pick) in best.1.iter().enumerate() {
+            if pick == 0 {
+                continue;
+            }
+            let order = &candidates[index][pick];
+            let action = start.order_action(&start.busters[index], order, &self.home_coords());
+            let mut buster = self.state.busters[index].clone();
+            if commands[index].starts_with("STUN") {
+                buster.stun_timer = 0;
             }
+            eprintln!(
+                "search: {} goes {:?} instead of {}",
+                buster.entity_id, order, commands[index]
+            );
+            commands[index] = format!("{} Saw that coming", action.to_command(&buster));
+            self.adopt_search_order(&mut buster, order, &action);
+            self.state.busters[index] = buster;
         }
+        commands
+    }
 
-        buster.state = if let Some(asshole) = self.find_best_asshole_in_los(buster) {
-            self.set_asshole_target(&asshole, buster);
-            BusterState::MovingToAsshole
-        } else if let Some(ghost) = &buster.busting_target_ref {
-            //target exists
-            if ghost.borrow().is_bustable(buster) {
-                //moved in radius
+    fn adopt_search_order(&mut self, buster: &mut Buster, order: &SearchMove, action: &SimAction) {
+        // the FSM's bookkeeping follows whatever actually gets sent, so next turn picks up from
+        // there and count_trappers sees who is really busting what
+        let stun_target = match action {
+            SimAction::Stun(asshole_id) => Some(*asshole_id),
+            SimAction::Move(_) => match order {
+                SearchMove::Stun(asshole_id) => Some(*asshole_id),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let SimAction::Wait = action {
+            return;
+        }
+        // a stun on the way is still an escort detour, anything else means the escort is over
+        if stun_target.is_none() && buster.escorting_id.is_some() {
+            self.end_escort(buster);
+        }
+        buster.stunning_target_id = None;
+        if let Some(asshole_id) = stun_target {
+            self.set_asshole_target(asshole_id, buster);
+        }
+        buster.state = match (action, order) {
+            (SimAction::Bust(ghost_id), _) => {
+                buster.busting_target_id = Some(*ghost_id);
                 BusterState::Busting
-            } else {
-                //too far away moving back
+            }
+            (SimAction::Stun(_), _) => {
+                buster.stun_timer = STUN_COOLDOWN;
+                BusterState::Stunning
+            }
+            (SimAction::Release, _) => BusterState::Releasing,
+            (SimAction::Eject(coords), _) => {
+                buster.eject_target = coords.clone();
+                buster.eject_catcher_id = None;
+                BusterState::EjectingToHome
+            }
+            (SimAction::Move(_), SearchMove::Bust(ghost_id)) => {
+                buster.busting_target_id = Some(*ghost_id);
                 BusterState::MovingToGhost
             }
-        } else {
-            //target no longer exists
-            BusterState::Searching
-        }
-    }
-    fn do_searching_transition(&self, buster: &mut Buster) -> () {
-        if self.turn_count >= START_CAMPING_TURN {
-            buster.state = BusterState::MovingToCamp;
-        } else if buster.coords.eq(&(*buster).movement_target) {
-            self.set_new_buster_movement_coords(buster);
-        } else if let Some(asshole) = self.find_best_asshole_in_los(buster) {
-            buster.state = BusterState::MovingToAsshole;
-            self.set_asshole_target(&asshole, buster);
-        } else if let Some(_) = self.find_ghost_in_radius_of_buster(buster) {
-            buster.state = BusterState::MovingToGhost;
-            buster.busting_target_ref = self.find_best_ghost_in_los(buster).cloned();
-        } else if let Some(ghost_being_targetted) = self.steal_target_ghost_reference(buster) {
-            buster.state = BusterState::MovingToGhost;
-            buster.busting_target_ref = Some(ghost_being_targetted);
-        }
-    }
-    fn do_busting_transition(&self, buster: &mut Buster) -> () {
-        buster.state = if buster.has_ghost {
-            BusterState::MovingToHome
-        } else {
-            if let Some(asshole) = self.find_best_asshole_in_los(buster) {
-                self.set_asshole_target(&asshole, buster);
-                BusterState::MovingToAsshole
-            } else {
+            (SimAction::Move(_), SearchMove::Stun(_)) => BusterState::MovingToAsshole,
+            (SimAction::Move(_), SearchMove::GoHome) => BusterState::MovingToHome,
+            (SimAction::Move(coords), _) => {
+                buster.busting_target_id = None;
+                buster.movement_target = coords.clone();
                 BusterState::Searching
             }
+            (SimAction::Wait, _) => buster.state,
         };
     }
-    fn do_stunning_transition(&self, buster: &mut Buster) -> () {
-        buster.state = if !buster.can_stun() {
-            BusterState::Searching
-        } else if let Some(asshole) = &mut buster.stunning_target_ref {
-            //successfully stunned
-            asshole.borrow_mut().state = BusterState::NotTargetted;
-            buster.stunning_target_ref = None;
-            BusterState::Searching
-        } else {
-            // he ran off?
-            BusterState::MovingToAsshole
-        };
-    }
-    fn do_home_move_transition(&self, buster: &mut Buster) -> () {
-        buster.state = if buster.has_ghost {
-            BusterState::Releasing
-        } else {
-            BusterState::Searching
-        };
-    }
-    fn do_release_transition(&self, buster: &mut Buster) -> () {
-        buster.state = BusterState::Searching;
-    }
 }
 
-impl StateMachine {
-    //ticks
-    fn update_tick(
-        &mut self,
-        new_ghosts: Vec<Rc<RefCell<Ghost>>>,
-        new_good: Vec<Rc<RefCell<Buster>>>,
-        new_evil: Vec<Rc<RefCell<Buster>>>,
-    ) -> () {
-        self.update_ghosts(new_ghosts);
-        if self.good_busters.is_empty() {
-            self.good_busters = new_good;
-            self.throw_and_assign_coords(false);
-            self.bad_busters = new_evil;
-        } else {
-            self.update_good(new_good);
-            self.update_evil(new_evil);
+// where the referee lines come from and where our commands go. --record keeps a game in a
+// replay file, --replay feeds one back through the state machine and stops at the first
+// command that comes out different
+struct GameIo {
+    seed: u64,
+    recorder: Option<std::fs::File>,
+    replay_inputs: std::collections::VecDeque<String>,
+    replay_outputs: std::collections::VecDeque<String>,
+    replaying: bool,
+    turn: i32,
+}
+
+impl GameIo {
+    fn open(params: &mut StrategyParams) -> GameIo {
+        let args: Vec<String> = std::env::args().collect();
+        let mut game_io = GameIo {
+            seed: match arg_value(&args, "--seed", "CB_SEED") {
+                Some(seed) => parse_input!(seed, u64),
+                None => rand::random::<u64>(),
+            },
+            recorder: None,
+            replay_inputs: std::collections::VecDeque::new(),
+            replay_outputs: std::collections::VecDeque::new(),
+            replaying: false,
+            turn: 0,
+        };
+        if let Some(path) = arg_value(&args, "--replay", "CB_REPLAY") {
+            let contents = std::fs::read_to_string(&path)
+                .unwrap_or_else(|err| panic!("couldn't read replay {}: {}", path, err));
+            for line in contents.lines() {
+                let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+                match kind {
+                    "seed" => game_io.seed = parse_input!(rest, u64),
+                    "param" => {
+                        let rest = rest.split('#').next().unwrap();
+                        if let Some((key, value)) = rest.split_once('=') {
+                            params.apply(key.trim(), value.trim());
+                        }
+                    }
+                    "in" => game_io.replay_inputs.push_back(rest.to_string()),
+                    "out" => game_io.replay_outputs.push_back(rest.to_string()),
+                    _ => eprintln!("ignoring replay line {}", line),
+                }
+            }
+            game_io.replaying = true;
+        } else if let Some(path) = arg_value(&args, "--record", "CB_RECORD") {
+            // no buffering, whatever was read before a panic has to be on disk
+            match std::fs::File::create(&path) {
+                Ok(file) => game_io.recorder = Some(file),
+                Err(err) => eprintln!("couldn't record to {}: {}", path, err),
+            }
+            game_io.record("seed", &game_io.seed.to_string());
+            for line in params.to_config().lines() {
+                game_io.record("param", line);
+            }
         }
-        self.do_global_ping();
-        self.turn_count += 1;
-        if self.turn_count == START_CAMPING_TURN {
-            self.throw_and_assign_coords(true);
+        game_io
+    }
+    fn record(&mut self, kind: &str, line: &str) {
+        if let Some(recorder) = &mut self.recorder {
+            let _ = writeln!(recorder, "{} {}", kind, line);
         }
-        eprintln!("global_stack_size: {}", self.global_ping_stack.len());
-        eprintln!("turn count: {}", self.turn_count);
     }
-    fn player_tick(&mut self, player_ind: usize) -> String {
-        let player_rc = &self.good_busters[player_ind];
-        {
-            let mut player = player_rc.as_ref().borrow_mut();
-            if player.is_stunned {
-                player.state = BusterState::Stunned
-            } else {
-                self.state_slide(player);
-            }
+    fn read_line(&mut self) -> String {
+        if self.replaying {
+            return match self.replay_inputs.pop_front() {
+                Some(line) => line,
+                None => {
+                    eprintln!("replay matched all {} turns", self.turn);
+                    std::process::exit(0);
+                }
+            };
         }
-        {
-            let mut player = player_rc.as_ref().borrow_mut();
-            player.tick();
+        let mut input_line = String::new();
+        io::stdin().read_line(&mut input_line).unwrap();
+        let line = input_line.trim_end().to_string();
+        self.record("in", &line);
+        line
+    }
+    fn write_command(&mut self, command: &str) {
+        if self.replaying {
+            // the referee stops listening after the last turn, so that one often has no output
+            let recorded = match self.replay_outputs.pop_front() {
+                Some(recorded) => recorded,
+                None => {
+                    eprintln!("replay matched all {} recorded turns", self.turn - 1);
+                    std::process::exit(0);
+                }
+            };
+            if recorded != command {
+                eprintln!(
+                    "replay differs on turn {}: recorded \"{}\", got \"{}\"",
+                    self.turn, recorded, command
+                );
+                std::process::exit(1);
+            }
+            return;
         }
-        let player = player_rc.as_ref().borrow_mut();
-        let result = self.do_state(player);
-        result
+        println!("{}", command);
+        self.record("out", command);
     }
 }
 
 fn main() {
-    let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
+    let mut params = StrategyParams::load();
+    let mut game_io = GameIo::open(&mut params);
+    let fsm_trace = FsmTrace::open();
+    let input_line = game_io.read_line();
     let busters_per_player = parse_input!(input_line, i32); // the amount of busters you control
-    let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
+    let input_line = game_io.read_line();
     let ghost_count = parse_input!(input_line, i32); // the amount of ghosts on the map
-    let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
+    let input_line = game_io.read_line();
     let my_team_id = parse_input!(input_line, i32); // if this is 0, your base is on the top left of the map, if it is one, on the bottom right
-    let mut game_machine = StateMachine::new(my_team_id);
+    let mut game_machine =
+        StateMachine::new(my_team_id, ghost_count, params, game_io.seed, fsm_trace);
     // game loop
     loop {
-        let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        let input_line = game_io.read_line();
+        let turn_start = Instant::now();
         let entities = parse_input!(input_line, i32); // the number of busters and ghosts visible to you
 
         let mut ghost_tick_vec = Vec::new();
@@ -1149,8 +4418,7 @@ fn main() {
         let mut evil_tick_vec = Vec::new();
 
         for i in 0..entities as usize {
-            let mut input_line = String::new();
-            io::stdin().read_line(&mut input_line).unwrap();
+            let input_line = game_io.read_line();
             let inputs = input_line.split(" ").collect::<Vec<_>>();
             let entity_id = parse_input!(inputs[0], i32); // buster id or ghost id
             let x = parse_input!(inputs[1], i32);
@@ -1161,23 +4429,280 @@ fn main() {
             match entity_type {
                 -1 => {
                     let new_entity = Ghost::new(entity_id, x, y, value, state);
-                    ghost_tick_vec.push(Rc::clone(&new_entity));
+                    ghost_tick_vec.push(new_entity);
                 }
                 bust if bust == my_team_id => {
-                    let new_entity = Buster::new(entity_id, x, y, state == 1, state == 2);
-                    good_tick_vec.push(Rc::clone(&new_entity))
+                    let new_entity = Buster::new(entity_id, x, y, state == 1, state == 2, value);
+                    good_tick_vec.push(new_entity)
                 }
                 _ => {
-                    let new_entity = Buster::new_opponent(entity_id, x, y, state == 1, state == 2);
-                    evil_tick_vec.push(Rc::clone(&new_entity))
+                    let new_entity = Opponent::new(entity_id, x, y, state == 1, state == 2, value);
+                    evil_tick_vec.push(new_entity)
                 }
             }
         }
-        game_machine.update_tick(ghost_tick_vec, good_tick_vec, evil_tick_vec);
+        game_io.turn += 1;
+        let turn = game_io.turn;
+        // say which turn blew up, the replay file has everything to get back to it
+        let commands = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            game_machine.update_tick(ghost_tick_vec, good_tick_vec, evil_tick_vec);
+            let commands = (0..busters_per_player as usize)
+                .map(|i| game_machine.player_tick(i))
+                .collect::<Vec<String>>();
+            if game_machine.params.use_search {
+                game_machine.search_turn(commands, turn_start)
+            } else {
+                commands
+            }
+        }))
+        .unwrap_or_else(|panic| {
+            eprintln!("panicked on turn {}", turn);
+            std::panic::resume_unwind(panic)
+        });
+        for command in commands.iter() {
+            game_io.write_command(command);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn home() -> Coord {
+        Coord::new(0, 0)
+    }
+    fn enemy_home() -> Coord {
+        Coord::new(MAP_RIGHT_WALL, MAP_BOTTOM_WALL)
+    }
+    fn machine() -> StateMachine {
+        StateMachine::new(0, 0, StrategyParams::new(), 1, FsmTrace { file: None })
+    }
+    fn state_with(busters: Vec<Buster>, opponents: Vec<Opponent>, ghosts: Vec<Ghost>) -> GameState {
+        let mut state = GameState::new();
+        state.busters = busters;
+        state.opponents = opponents;
+        state.ghosts = ghosts;
+        state
+    }
+
+    #[test]
+    fn busting_spot_backs_out_of_the_too_close_zone() {
+        let machine = machine();
+        let buster = Buster::new(0, 7602, 3937, false, false, -1);
+        for ghost in [
+            Ghost::new(7, 8200, 4300, 0, 10),
+            Ghost::new(7, 8200, 4300, 1, 10),
+        ] {
+            let spot = machine.busting_spot(&buster, &ghost);
+            assert_ne!(spot, buster.coords);
+            assert!(!spot.is_in_radius(&ghost.coords, INNER_BUSTING_RADIUS));
+            assert!(spot.is_in_radius(&ghost.coords, OUTER_BUSTING_RADIUS));
+        }
+    }
+
+    #[test]
+    fn one_on_one_tie_at_zero_stamina_goes_to_nobody() {
+        let mut state = state_with(
+            vec![Buster::new(0, 5000, 5000, false, false, -1)],
+            vec![Opponent::new(3, 7000, 5000, false, false, -1)],
+            vec![Ghost::new(7, 6000, 5000, 2, 1)],
+        );
+        state.simulate(
+            &[SimAction::Bust(7)],
+            &[SimAction::Bust(7)],
+            &home(),
+            &enemy_home(),
+        );
+        let ghost = state.ghost(7).unwrap();
+        assert_eq!(ghost.stamina, 0);
+        assert_eq!(ghost.enemy_trapping, 1);
+        assert!(!state.busters[0].has_ghost && !state.opponents[0].has_ghost);
+    }
+
+    fn contested_machine(helper_x: i32) -> StateMachine {
+        let mut machine = machine();
+        let mut ghost = Ghost::new(7, 6000, 5000, 2, 0);
+        ghost.enemy_trapping = 1;
+        machine.state = state_with(
+            vec![
+                Buster::new(0, 5000, 5000, false, false, -1),
+                Buster::new(1, helper_x, 5000, false, false, -1),
+            ],
+            vec![Opponent::new(3, 7000, 5000, false, false, -1)],
+            vec![ghost],
+        );
+        machine.team_plan = vec![
+            PlanEntry {
+                buster_id: 0,
+                ghost: Some(7),
+                asshole: None,
+                escort: None,
+            },
+            PlanEntry {
+                buster_id: 1,
+                ghost: None,
+                asshole: None,
+                escort: None,
+            },
+        ];
+        machine
+    }
+
+    #[test]
+    fn tied_ghost_gets_a_free_teammate_sent_over() {
+        let mut machine = contested_machine(4000);
+        machine.resolve_contests();
+        assert!(machine.team_plan.iter().all(|entry| entry.ghost == Some(7)));
+    }
+
+    #[test]
+    fn tied_ghost_is_left_when_nobody_can_get_there_in_time() {
+        let mut machine = contested_machine(MAP_RIGHT_WALL);
+        machine.resolve_contests();
+        assert!(machine.team_plan.iter().all(|entry| entry.ghost.is_none()));
+    }
 
-        for i in 0..busters_per_player as usize {
-            let result = game_machine.player_tick(i);
-            println!("{}", result);
+    #[test]
+    fn acting_on_a_missing_target_keeps_searching() {
+        let mut machine = machine();
+        for state in [
+            BusterState::Busting,
+            BusterState::MovingToGhost,
+            BusterState::Herding,
+            BusterState::MovingToAsshole,
+            BusterState::Escorting,
+        ] {
+            let mut buster = Buster::new(0, 5000, 5000, false, false, -1);
+            buster.movement_target = Coord::new(6000, 6000);
+            buster.busting_target_id = Some(99);
+            buster.stunning_target_id = Some(98);
+            buster.escorting_id = Some(97);
+            buster.state = state;
+            assert!(machine.do_state(&mut buster).starts_with("MOVE 6000 6000"));
         }
     }
+
+    #[test]
+    fn loose_ghost_flees_straight_away_from_the_closest_buster() {
+        let mut state = state_with(
+            vec![Buster::new(0, 5000, 5000, false, false, -1)],
+            Vec::new(),
+            vec![Ghost::new(7, 6000, 5000, 0, 10)],
+        );
+        state.simulate(&[SimAction::Wait], &[], &home(), &enemy_home());
+        assert_eq!(
+            state.ghost(7).unwrap().coords,
+            Coord::new(6000 + GHOST_FLEE_DISTANCE, 5000)
+        );
+    }
+
+    #[test]
+    fn trapped_ghost_holds_still() {
+        let mut state = state_with(
+            vec![Buster::new(0, 5000, 5000, false, false, -1)],
+            Vec::new(),
+            vec![Ghost::new(7, 6000, 5000, 0, 10)],
+        );
+        state.simulate(&[SimAction::Bust(7)], &[], &home(), &enemy_home());
+        let ghost = state.ghost(7).unwrap();
+        assert_eq!(ghost.coords, Coord::new(6000, 5000));
+        assert_eq!(ghost.stamina, 9);
+    }
+
+    #[test]
+    fn stun_cancels_the_victims_action_and_drops_its_ghost() {
+        let mut state = state_with(
+            vec![Buster::new(0, 5000, 5000, true, false, 7)],
+            vec![Opponent::new(3, 5500, 5000, false, false, -1)],
+            Vec::new(),
+        );
+        state.simulate(
+            &[SimAction::Move(home())],
+            &[SimAction::Stun(0)],
+            &home(),
+            &enemy_home(),
+        );
+        let buster = &state.busters[0];
+        assert!(buster.is_stunned);
+        assert!(!buster.has_ghost);
+        assert_eq!(buster.coords, Coord::new(5000, 5000));
+        assert_eq!(state.ghost(7).unwrap().coords, Coord::new(5000, 5000));
+        assert_eq!(state.opponents[0].stun_timer, STUN_COOLDOWN - 1);
+    }
+
+    #[test]
+    fn stun_out_of_range_does_nothing() {
+        let mut state = state_with(
+            vec![Buster::new(0, 5000, 5000, true, false, 7)],
+            vec![Opponent::new(
+                3,
+                5000 + STUN_RADIUS + 1,
+                5000,
+                false,
+                false,
+                -1,
+            )],
+            Vec::new(),
+        );
+        state.simulate(
+            &[SimAction::Wait],
+            &[SimAction::Stun(0)],
+            &home(),
+            &enemy_home(),
+        );
+        assert!(!state.busters[0].is_stunned);
+        assert!(state.busters[0].has_ghost);
+        assert!(state.opponents[0].can_stun());
+    }
+
+    #[test]
+    fn release_scores_only_inside_the_base_radius() {
+        let inside = BASE_RELEASE_RADIUS * 7 / 10;
+        let outside = BASE_RELEASE_RADIUS * 8 / 10;
+        let mut state = state_with(
+            vec![
+                Buster::new(0, inside, inside, true, false, 7),
+                Buster::new(1, outside, outside, true, false, 8),
+            ],
+            Vec::new(),
+            Vec::new(),
+        );
+        state.simulate(
+            &[SimAction::Release, SimAction::Release],
+            &[],
+            &home(),
+            &enemy_home(),
+        );
+        assert_eq!(state.our_score, 1);
+        assert!(state.ghost(7).is_none());
+        assert_eq!(state.ghost(8).unwrap().coords, Coord::new(outside, outside));
+    }
+
+    #[test]
+    fn eject_lands_at_most_a_throw_away() {
+        let mut state = state_with(
+            vec![
+                Buster::new(0, 8000, 4000, true, false, 7),
+                Buster::new(1, 8000, 6000, true, false, 8),
+            ],
+            Vec::new(),
+            Vec::new(),
+        );
+        state.simulate(
+            &[
+                SimAction::Eject(Coord::new(0, 4000)),
+                SimAction::Eject(Coord::new(8000, 6500)),
+            ],
+            &[],
+            &home(),
+            &enemy_home(),
+        );
+        assert_eq!(
+            state.ghost(7).unwrap().coords,
+            Coord::new(8000 - EJECT_THROW_DISTANCE, 4000)
+        );
+        assert_eq!(state.ghost(8).unwrap().coords, Coord::new(8000, 6500));
+        assert!(!state.busters[0].has_ghost && !state.busters[1].has_ghost);
+    }
 }
\ No newline at end of file
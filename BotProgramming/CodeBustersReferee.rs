@@ -0,0 +1,752 @@
+// Local referee for CodeBusters.rs
+//
+// Plays the full Code Busters rules between two bot executables, speaking the exact
+// stdin/stdout protocol the arena uses. Only std is needed:
+//
+//     rustc -O BotProgramming/CodeBustersReferee.rs -o cb_referee
+//     ./cb_referee [options] "<bot0 command>" "<bot1 command>"
+//
+// options:
+//     --seed N         first map seed (default 1)
+//     --games N        number of games, sides swap every game (default 1)
+//     --busters N      busters per player (default 3)
+//     --ghosts N       ghosts on the map (default 15)
+//     --turns N        turn limit (default 400)
+//     --verbose        print every turn's commands to stderr
+//     --show-stderr    let the bots' stderr through
+//     --timeout MS     time a bot gets to answer a turn (default 100)
+//     --first-timeout MS  time it gets for the first turn (default 1000)
+//
+// a bot that dies or doesn't answer in time loses the game. every game prints one `RESULT`
+// line, the last line is a `SUMMARY` for bot0 vs bot1.
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+const MAP_RIGHT_WALL: i32 = 16000;
+const MAP_BOTTOM_WALL: i32 = 9000;
+
+const BUSTER_MOVE_RANGE: f64 = 800.0;
+const GHOST_MOVE_RANGE: f64 = 400.0;
+
+const OUTER_BUSTING_RADIUS: f64 = 1760.0;
+const INNER_BUSTING_RADIUS: f64 = 900.0;
+const STUN_RADIUS: f64 = 1760.0;
+const BASE_RELEASE_RADIUS: f64 = 1600.0;
+const LINE_OF_SIGHT_RADIUS: f64 = 2200.0;
+const RADAR_RADIUS: f64 = 4400.0;
+const EJECT_RANGE: f64 = 1760.0;
+
+const STUN_DURATION: i32 = 10;
+const STUN_COOLDOWN: i32 = 20;
+
+const GHOST_STAMINAS: [i32; 3] = [3, 15, 40];
+const GHOST_SPAWN_BASE_MARGIN: f64 = 3000.0;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Coord {
+    x: i32,
+    y: i32,
+}
+
+impl Coord {
+    fn new(x: i32, y: i32) -> Coord {
+        Coord { x, y }
+    }
+    fn distance_to(&self, other: &Coord) -> f64 {
+        let dx = (self.x - other.x) as f64;
+        let dy = (self.y - other.y) as f64;
+        (dx * dx + dy * dy).sqrt()
+    }
+    fn clamped(x: f64, y: f64) -> Coord {
+        Coord::new(
+            (x.round() as i32).clamp(0, MAP_RIGHT_WALL),
+            (y.round() as i32).clamp(0, MAP_BOTTOM_WALL),
+        )
+    }
+    fn step_towards(&self, target: &Coord, max_step: f64) -> Coord {
+        let dist = self.distance_to(target);
+        if dist <= max_step {
+            return Coord::clamped(target.x as f64, target.y as f64);
+        }
+        let factor = max_step / dist;
+        Coord::clamped(
+            self.x as f64 + (target.x - self.x) as f64 * factor,
+            self.y as f64 + (target.y - self.y) as f64 * factor,
+        )
+    }
+    fn mirrored(&self) -> Coord {
+        Coord::new(MAP_RIGHT_WALL - self.x, MAP_BOTTOM_WALL - self.y)
+    }
+}
+
+fn base_of(team: usize) -> Coord {
+    if team == 0 {
+        Coord::new(0, 0)
+    } else {
+        Coord::new(MAP_RIGHT_WALL, MAP_BOTTOM_WALL)
+    }
+}
+
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    // xorshift64*, good enough for map generation and keeps the referee std only
+    fn new(seed: u64) -> Rng {
+        Rng {
+            state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1,
+        }
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+    fn gen_range(&mut self, low: i32, high: i32) -> i32 {
+        low + (self.next_u64() % (high - low) as u64) as i32
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RefBuster {
+    entity_id: i32,
+    team: usize,
+    coords: Coord,
+    carrying: Option<i32>,
+    busting: Option<i32>,
+    stunned_for: i32,
+    stun_cooldown: i32,
+    radar_used: bool,
+    radar_active: bool,
+}
+
+#[derive(Debug, Clone)]
+struct RefGhost {
+    entity_id: i32,
+    coords: Coord,
+    stamina: i32,
+    people_trapping: i32,
+    on_map: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    Wait,
+    Move(Coord),
+    Bust(i32),
+    Stun(i32),
+    Release,
+    Radar,
+    Eject(Coord),
+}
+
+impl Action {
+    fn parse(line: &str) -> Action {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let number = |index: usize| words.get(index).and_then(|word| word.parse::<i32>().ok());
+        match words.first().copied() {
+            Some("MOVE") => match (number(1), number(2)) {
+                (Some(x), Some(y)) => Action::Move(Coord::new(x, y)),
+                _ => Action::Wait,
+            },
+            Some("BUST") => number(1).map_or(Action::Wait, Action::Bust),
+            Some("STUN") => number(1).map_or(Action::Wait, Action::Stun),
+            Some("RELEASE") => Action::Release,
+            Some("RADAR") => Action::Radar,
+            Some("EJECT") => match (number(1), number(2)) {
+                (Some(x), Some(y)) => Action::Eject(Coord::new(x, y)),
+                _ => Action::Wait,
+            },
+            _ => Action::Wait,
+        }
+    }
+}
+
+struct Game {
+    busters_per_player: usize,
+    ghost_count: usize,
+    max_turns: i32,
+    busters: Vec<RefBuster>,
+    ghosts: Vec<RefGhost>,
+    scores: [i32; 2],
+    turn: i32,
+}
+
+impl Game {
+    // constructors
+    fn new(seed: u64, busters_per_player: usize, ghost_count: usize, max_turns: i32) -> Game {
+        let mut rng = Rng::new(seed);
+        let mut busters = Vec::new();
+        for team in 0..2 {
+            for i in 0..busters_per_player {
+                // spread along the diagonal facing the map, like the arena does
+                let offset = (i as f64 - (busters_per_player as f64 - 1.0) / 2.0) * 600.0;
+                let start = Coord::clamped(1600.0 + offset, 1600.0 - offset);
+                busters.push(RefBuster {
+                    entity_id: (team * busters_per_player + i) as i32,
+                    team,
+                    coords: if team == 0 { start } else { start.mirrored() },
+                    carrying: None,
+                    busting: None,
+                    stunned_for: 0,
+                    stun_cooldown: 0,
+                    radar_used: false,
+                    radar_active: false,
+                });
+            }
+        }
+
+        let mut ghosts = Vec::new();
+        let mut push_ghost = |coords: Coord, stamina: i32| {
+            ghosts.push(RefGhost {
+                entity_id: ghosts.len() as i32,
+                coords,
+                stamina,
+                people_trapping: 0,
+                on_map: true,
+            });
+        };
+        if ghost_count % 2 == 1 {
+            push_ghost(
+                Coord::new(MAP_RIGHT_WALL / 2, MAP_BOTTOM_WALL / 2),
+                GHOST_STAMINAS[0],
+            );
+        }
+        for _ in 0..ghost_count / 2 {
+            let coords = loop {
+                let candidate = Coord::new(
+                    rng.gen_range(0, MAP_RIGHT_WALL + 1),
+                    rng.gen_range(0, MAP_BOTTOM_WALL + 1),
+                );
+                if candidate.distance_to(&base_of(0)) > GHOST_SPAWN_BASE_MARGIN
+                    && candidate.distance_to(&base_of(1)) > GHOST_SPAWN_BASE_MARGIN
+                {
+                    break candidate;
+                }
+            };
+            let stamina = GHOST_STAMINAS[rng.gen_range(0, GHOST_STAMINAS.len() as i32) as usize];
+            push_ghost(coords, stamina);
+            push_ghost(coords.mirrored(), stamina);
+        }
+
+        Game {
+            busters_per_player,
+            ghost_count,
+            max_turns,
+            busters,
+            ghosts,
+            scores: [0, 0],
+            turn: 0,
+        }
+    }
+}
+
+impl Game {
+    //protocol
+    fn init_lines(&self, team: usize) -> Vec<String> {
+        vec![
+            self.busters_per_player.to_string(),
+            self.ghost_count.to_string(),
+            team.to_string(),
+        ]
+    }
+    fn sees(&self, team: usize, coords: &Coord) -> bool {
+        self.busters
+            .iter()
+            .filter(|buster| buster.team == team)
+            .any(|buster| {
+                let radius = if buster.radar_active {
+                    RADAR_RADIUS
+                } else {
+                    LINE_OF_SIGHT_RADIUS
+                };
+                buster.coords.distance_to(coords) <= radius
+            })
+    }
+    fn buster_line(&self, buster: &RefBuster) -> String {
+        let (state, value) = if buster.stunned_for > 0 {
+            (2, buster.stunned_for)
+        } else if let Some(ghost_id) = buster.carrying {
+            (1, ghost_id)
+        } else if let Some(ghost_id) = buster.busting {
+            (3, ghost_id)
+        } else {
+            (0, -1)
+        };
+        format!(
+            "{} {} {} {} {} {}",
+            buster.entity_id, buster.coords.x, buster.coords.y, buster.team, state, value
+        )
+    }
+    fn turn_lines(&self, team: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        for buster in self.busters.iter().filter(|buster| buster.team == team) {
+            lines.push(self.buster_line(buster));
+        }
+        for buster in self.busters.iter().filter(|buster| buster.team != team) {
+            if self.sees(team, &buster.coords) {
+                lines.push(self.buster_line(buster));
+            }
+        }
+        for ghost in self.ghosts.iter().filter(|ghost| ghost.on_map) {
+            if self.sees(team, &ghost.coords) {
+                lines.push(format!(
+                    "{} {} {} -1 {} {}",
+                    ghost.entity_id,
+                    ghost.coords.x,
+                    ghost.coords.y,
+                    ghost.stamina,
+                    ghost.people_trapping
+                ));
+            }
+        }
+        lines.insert(0, lines.len().to_string());
+        lines
+    }
+}
+
+impl Game {
+    //rules
+    fn drop_ghost(&mut self, buster_index: usize, coords: Coord) -> Option<usize> {
+        let ghost_id = self.busters[buster_index].carrying.take()?;
+        let ghost = &mut self.ghosts[ghost_id as usize];
+        ghost.coords = coords;
+        ghost.stamina = 0;
+        ghost.on_map = true;
+        Some(ghost_id as usize)
+    }
+    fn step(&mut self, mut actions: Vec<Action>) {
+        self.turn += 1;
+        let start_coords: Vec<Coord> = self.busters.iter().map(|buster| buster.coords).collect();
+
+        // stunned busters sit the turn out
+        for (index, buster) in self.busters.iter_mut().enumerate() {
+            if buster.stun_cooldown > 0 {
+                buster.stun_cooldown -= 1;
+            }
+            if buster.stunned_for > 0 {
+                buster.stunned_for -= 1;
+                actions[index] = Action::Wait;
+            }
+            buster.busting = None;
+            buster.radar_active = false;
+        }
+
+        // ghosts that left a buster's hands this turn don't flee until the next one
+        let mut dropped = Vec::new();
+
+        // stuns resolve simultaneously and cancel the victim's action
+        let mut victims = Vec::new();
+        for (index, action) in actions.iter().enumerate() {
+            if let Action::Stun(target_id) = *action {
+                let stunner = &self.busters[index];
+                let target = self
+                    .busters
+                    .iter()
+                    .position(|buster| buster.entity_id == target_id);
+                match target {
+                    Some(target)
+                        if stunner.stun_cooldown == 0
+                            && target != index
+                            && stunner.coords.distance_to(&self.busters[target].coords)
+                                <= STUN_RADIUS =>
+                    {
+                        self.busters[index].stun_cooldown = STUN_COOLDOWN;
+                        victims.push(target);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for victim in victims {
+            self.busters[victim].stunned_for = STUN_DURATION;
+            let coords = self.busters[victim].coords;
+            dropped.extend(self.drop_ghost(victim, coords));
+            actions[victim] = Action::Wait;
+        }
+
+        // moves and releases
+        for (index, action) in actions.iter().enumerate() {
+            match action.clone() {
+                Action::Move(target) => {
+                    let coords = self.busters[index]
+                        .coords
+                        .step_towards(&target, BUSTER_MOVE_RANGE);
+                    self.busters[index].coords = coords;
+                    if let Some(ghost_id) = self.busters[index].carrying {
+                        self.ghosts[ghost_id as usize].coords = coords;
+                    }
+                }
+                Action::Release => {
+                    let buster = &self.busters[index];
+                    if let Some(ghost_id) = buster.carrying {
+                        if buster.coords.distance_to(&base_of(buster.team)) <= BASE_RELEASE_RADIUS {
+                            self.scores[buster.team] += 1;
+                            self.busters[index].carrying = None;
+                            self.ghosts[ghost_id as usize].on_map = false;
+                        } else {
+                            let coords = buster.coords;
+                            dropped.extend(self.drop_ghost(index, coords));
+                        }
+                    }
+                }
+                Action::Eject(target) => {
+                    let coords = self.busters[index]
+                        .coords
+                        .step_towards(&target, EJECT_RANGE);
+                    dropped.extend(self.drop_ghost(index, coords));
+                }
+                Action::Radar => {
+                    // one per buster per game, widens its sight for the next input only
+                    let buster = &mut self.busters[index];
+                    if !buster.radar_used {
+                        buster.radar_used = true;
+                        buster.radar_active = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // busts, ghosts at 0 stamina go to the team trapping with more busters
+        let mut trappers: Vec<Vec<usize>> = vec![Vec::new(); self.ghosts.len()];
+        for (index, action) in actions.iter().enumerate() {
+            if let Action::Bust(ghost_id) = *action {
+                let buster = &self.busters[index];
+                let ghost = match self.ghosts.get(ghost_id as usize) {
+                    Some(ghost) if ghost.on_map => ghost,
+                    _ => continue,
+                };
+                let dist = buster.coords.distance_to(&ghost.coords);
+                if buster.carrying.is_none()
+                    && (INNER_BUSTING_RADIUS..=OUTER_BUSTING_RADIUS).contains(&dist)
+                {
+                    trappers[ghost_id as usize].push(index);
+                    self.busters[index].busting = Some(ghost_id);
+                }
+            }
+        }
+        for (ghost_index, ghost_trappers) in trappers.iter().enumerate() {
+            self.ghosts[ghost_index].people_trapping = ghost_trappers.len() as i32;
+            if ghost_trappers.is_empty() {
+                continue;
+            }
+            let ghost = &mut self.ghosts[ghost_index];
+            ghost.stamina = (ghost.stamina - ghost_trappers.len() as i32).max(0);
+            if ghost.stamina > 0 {
+                continue;
+            }
+            let team_count = |team: usize| {
+                ghost_trappers
+                    .iter()
+                    .filter(|&&index| self.busters[index].team == team)
+                    .count()
+            };
+            let winning_team = match (team_count(0), team_count(1)) {
+                (a, b) if a > b => 0,
+                (a, b) if b > a => 1,
+                _ => continue,
+            };
+            let ghost_coords = self.ghosts[ghost_index].coords;
+            let catcher = ghost_trappers
+                .iter()
+                .copied()
+                .filter(|&index| self.busters[index].team == winning_team)
+                .min_by(|&a, &b| {
+                    let dist_a = self.busters[a].coords.distance_to(&ghost_coords);
+                    let dist_b = self.busters[b].coords.distance_to(&ghost_coords);
+                    dist_a.partial_cmp(&dist_b).unwrap()
+                })
+                .unwrap();
+            self.busters[catcher].carrying = Some(ghost_index as i32);
+            self.busters[catcher].busting = None;
+            self.ghosts[ghost_index].on_map = false;
+            self.ghosts[ghost_index].people_trapping = 0;
+        }
+
+        // untrapped ghosts flee 400 from the closest buster they saw at the start of the turn
+        for (ghost_index, ghost_trappers) in trappers.iter().enumerate() {
+            let ghost = &self.ghosts[ghost_index];
+            if !ghost.on_map || !ghost_trappers.is_empty() || dropped.contains(&ghost_index) {
+                continue;
+            }
+            let mut closest_dist = f64::MAX;
+            let mut closest = Vec::new();
+            for coords in start_coords.iter() {
+                let dist = coords.distance_to(&ghost.coords);
+                if dist > LINE_OF_SIGHT_RADIUS {
+                    continue;
+                }
+                if dist < closest_dist {
+                    closest_dist = dist;
+                    closest.clear();
+                }
+                if dist == closest_dist {
+                    closest.push(*coords);
+                }
+            }
+            if closest.is_empty() {
+                continue;
+            }
+            let avg_x =
+                closest.iter().map(|coords| coords.x as f64).sum::<f64>() / closest.len() as f64;
+            let avg_y =
+                closest.iter().map(|coords| coords.y as f64).sum::<f64>() / closest.len() as f64;
+            let dx = ghost.coords.x as f64 - avg_x;
+            let dy = ghost.coords.y as f64 - avg_y;
+            let len = (dx * dx + dy * dy).sqrt();
+            if len == 0.0 {
+                continue;
+            }
+            let new_coords = Coord::clamped(
+                ghost.coords.x as f64 + dx / len * GHOST_MOVE_RANGE,
+                ghost.coords.y as f64 + dy / len * GHOST_MOVE_RANGE,
+            );
+            self.ghosts[ghost_index].coords = new_coords;
+        }
+    }
+    fn is_over(&self) -> bool {
+        let ghosts_in_play = self.ghosts.iter().any(|ghost| ghost.on_map)
+            || self.busters.iter().any(|buster| buster.carrying.is_some());
+        self.turn >= self.max_turns
+            || !ghosts_in_play
+            || self
+                .scores
+                .iter()
+                .any(|&score| score as usize * 2 > self.ghost_count)
+    }
+}
+
+struct BotProcess {
+    child: Child,
+    stdin: ChildStdin,
+    // stdout is read on its own thread so a bot that hangs can't hang the referee
+    lines: Receiver<String>,
+    max_response_ms: u128,
+}
+
+impl BotProcess {
+    fn spawn(command: &str, show_stderr: bool) -> BotProcess {
+        let mut words = command.split_whitespace();
+        let program = words.next().expect("empty bot command");
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(if show_stderr {
+                Stdio::inherit()
+            } else {
+                Stdio::null()
+            })
+            .spawn()
+            .unwrap_or_else(|err| panic!("could not start {}: {}", command, err));
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in stdout.lines() {
+                match line {
+                    Ok(line) if sender.send(line.trim().to_string()).is_ok() => {}
+                    _ => break,
+                }
+            }
+        });
+        BotProcess {
+            child,
+            stdin,
+            lines,
+            max_response_ms: 0,
+        }
+    }
+    fn send(&mut self, lines: &[String]) -> bool {
+        for line in lines {
+            if writeln!(self.stdin, "{}", line).is_err() {
+                return false;
+            }
+        }
+        self.stdin.flush().is_ok()
+    }
+    fn receive(&mut self, count: usize, timeout: Duration) -> Result<Vec<String>, &'static str> {
+        let start = Instant::now();
+        let deadline = start + timeout;
+        let mut lines = Vec::new();
+        for _ in 0..count {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.lines.recv_timeout(left) {
+                Ok(line) => lines.push(line),
+                Err(RecvTimeoutError::Timeout) => return Err("timed out"),
+                Err(RecvTimeoutError::Disconnected) => return Err("crashed"),
+            }
+        }
+        self.max_response_ms = self.max_response_ms.max(start.elapsed().as_millis());
+        Ok(lines)
+    }
+}
+
+impl Drop for BotProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+struct GameResult {
+    scores: [i32; 2],
+    turns: i32,
+    crashed: Option<usize>,
+    max_response_ms: [u128; 2],
+}
+
+struct Limits {
+    verbose: bool,
+    show_stderr: bool,
+    first_timeout: Duration,
+    timeout: Duration,
+}
+
+fn play_game(game: &mut Game, commands: [&str; 2], limits: &Limits) -> GameResult {
+    let mut bots = [
+        BotProcess::spawn(commands[0], limits.show_stderr),
+        BotProcess::spawn(commands[1], limits.show_stderr),
+    ];
+    let mut crashed = None;
+    for (team, bot) in bots.iter_mut().enumerate() {
+        if !bot.send(&game.init_lines(team)) {
+            crashed = Some(team);
+        }
+    }
+
+    while crashed.is_none() && !game.is_over() {
+        let mut actions = vec![Action::Wait; game.busters.len()];
+        let timeout = if game.turn == 0 {
+            limits.first_timeout
+        } else {
+            limits.timeout
+        };
+        for (team, bot) in bots.iter_mut().enumerate() {
+            let lines = game.turn_lines(team);
+            let outputs = if bot.send(&lines) {
+                bot.receive(game.busters_per_player, timeout)
+            } else {
+                Err("crashed")
+            };
+            let outputs = match outputs {
+                Ok(outputs) => outputs,
+                Err(reason) => {
+                    eprintln!("team {} {} on turn {}", team, reason, game.turn + 1);
+                    crashed = Some(team);
+                    break;
+                }
+            };
+            for (i, output) in outputs.iter().enumerate() {
+                let index = team * game.busters_per_player + i;
+                actions[index] = Action::parse(output);
+                if limits.verbose {
+                    eprintln!("turn {} buster {}: {}", game.turn + 1, index, output);
+                }
+            }
+        }
+        if crashed.is_none() {
+            game.step(actions);
+            if limits.verbose {
+                for buster in game.busters.iter() {
+                    eprintln!("  {}", game.buster_line(buster));
+                }
+                eprintln!("  score {}-{}", game.scores[0], game.scores[1]);
+            }
+        }
+    }
+
+    GameResult {
+        scores: game.scores,
+        turns: game.turn,
+        crashed,
+        max_response_ms: [bots[0].max_response_ms, bots[1].max_response_ms],
+    }
+}
+
+fn main() {
+    let mut seed: u64 = 1;
+    let mut games = 1;
+    let mut busters_per_player = 3;
+    let mut ghost_count = 15;
+    let mut max_turns = 400;
+    let mut limits = Limits {
+        verbose: false,
+        show_stderr: false,
+        first_timeout: Duration::from_millis(1000),
+        timeout: Duration::from_millis(100),
+    };
+    let mut commands = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or_else(|| panic!("{} needs a number", name))
+        };
+        match arg.as_str() {
+            "--seed" => seed = value("--seed"),
+            "--games" => games = value("--games"),
+            "--busters" => busters_per_player = value("--busters") as usize,
+            "--ghosts" => ghost_count = value("--ghosts") as usize,
+            "--turns" => max_turns = value("--turns") as i32,
+            "--timeout" => limits.timeout = Duration::from_millis(value("--timeout")),
+            "--first-timeout" => {
+                limits.first_timeout = Duration::from_millis(value("--first-timeout"))
+            }
+            "--verbose" => limits.verbose = true,
+            "--show-stderr" => limits.show_stderr = true,
+            _ => commands.push(arg),
+        }
+    }
+    if commands.len() != 2 {
+        eprintln!("usage: cb_referee [options] \"<bot0 command>\" \"<bot1 command>\"");
+        std::process::exit(2);
+    }
+
+    // wins from bot0's point of view, whichever side it played
+    let (mut wins, mut losses, mut draws) = (0, 0, 0);
+    for game_index in 0..games {
+        let game_seed = seed + game_index;
+        let swapped = game_index % 2 == 1;
+        let sides = if swapped {
+            [commands[1].as_str(), commands[0].as_str()]
+        } else {
+            [commands[0].as_str(), commands[1].as_str()]
+        };
+        let mut game = Game::new(game_seed, busters_per_player, ghost_count, max_turns);
+        let result = play_game(&mut game, sides, &limits);
+
+        let bot0_team = if swapped { 1 } else { 0 };
+        let bot0_outcome = match result.crashed {
+            Some(team) if team == bot0_team => -1,
+            Some(_) => 1,
+            None => (result.scores[bot0_team] - result.scores[1 - bot0_team]).signum(),
+        };
+        match bot0_outcome {
+            1 => wins += 1,
+            -1 => losses += 1,
+            _ => draws += 1,
+        }
+        println!(
+            "RESULT seed={} bot0_team={} score={}-{} turns={} crashed={} max_ms={}/{}",
+            game_seed,
+            bot0_team,
+            result.scores[0],
+            result.scores[1],
+            result.turns,
+            result
+                .crashed
+                .map_or(String::from("none"), |team| team.to_string()),
+            result.max_response_ms[0],
+            result.max_response_ms[1]
+        );
+    }
+    println!("SUMMARY wins={} losses={} draws={}", wins, losses, draws);
+}
@@ -0,0 +1,427 @@
+// Parameter tuner for CodeBusters.rs
+//
+// Sweeps the bot's StrategyParams with SPSA against a frozen baseline, using the local
+// referee for the games. Only std is needed:
+//
+//     rustc -O BotProgramming/CodeBustersTuner.rs -o cb_tuner
+//     ./cb_tuner [options] --tune start_camping_turn:40:200 --tune camping_radius:1500:4000
+//
+// options:
+//     --referee CMD        referee executable (default ./cb_referee)
+//     --bot CMD            bot being tuned, must understand --config and --dump-params (default ./cb_bot)
+//     --baseline CMD       frozen opponent (default the bot with its compiled-in params)
+//     --params FILE        start from this config instead of the bot's defaults
+//     --tune NAME:MIN:MAX  a param to search and its range, repeat for more, only numeric params
+//     --iterations N       SPSA steps (default 50)
+//     --games N            games per side of every gradient estimate (default 20)
+//     --eval-every N       check the current point every N steps (default 10)
+//     --eval-games N       games for those checks (default 100)
+//     --jobs N             referee processes per evaluation (default 4)
+//     --seed N             first map seed, every evaluation gets fresh maps (default 1)
+//     --out FILE           where the best params found get written (default best_params.cfg)
+//
+// win rate counts draws as half, confidence intervals are 95% over games played.
+use std::process::Command;
+
+const SPSA_STEP: f64 = 0.2;
+const SPSA_PERTURBATION: f64 = 0.1;
+const SPSA_STABILITY: f64 = 5.0;
+const SPSA_STEP_DECAY: f64 = 0.602;
+const SPSA_PERTURBATION_DECAY: f64 = 0.101;
+const CONFIDENCE_Z: f64 = 1.96;
+
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng {
+            state: seed.wrapping_mul(0x9E3779B97F4A7C15) | 1,
+        }
+    }
+    fn next_u64(&mut self) -> u64 {
+        // xorshift64*
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+    fn sign(&mut self) -> f64 {
+        if self.next_u64() & 1 == 0 {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+}
+
+struct Tuned {
+    name: String,
+    min: f64,
+    max: f64,
+    is_float: bool,
+}
+
+impl Tuned {
+    fn parse(spec: &str) -> Tuned {
+        let parts: Vec<&str> = spec.split(':').collect();
+        if parts.len() != 3 {
+            panic!("--tune wants NAME:MIN:MAX, got {}", spec);
+        }
+        let bound = |text: &str| {
+            text.parse::<f64>()
+                .unwrap_or_else(|_| panic!("bad bound {} in {}", text, spec))
+        };
+        Tuned {
+            name: parts[0].to_string(),
+            min: bound(parts[1]),
+            max: bound(parts[2]),
+            is_float: false,
+        }
+    }
+    fn to_unit(&self, value: f64) -> f64 {
+        ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+    }
+    fn value_at(&self, unit: f64) -> String {
+        let value = self.min + unit.clamp(0.0, 1.0) * (self.max - self.min);
+        if self.is_float {
+            format!("{:.4}", value)
+        } else {
+            format!("{}", value.round() as i64)
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+struct Score {
+    wins: u64,
+    losses: u64,
+    draws: u64,
+}
+
+impl Score {
+    fn games(&self) -> u64 {
+        self.wins + self.losses + self.draws
+    }
+    fn win_rate(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.0;
+        }
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games() as f64
+    }
+    fn confidence(&self) -> f64 {
+        // spread of a per-game score of 1, 0.5 or 0
+        let games = self.games().max(1) as f64;
+        let mean = self.win_rate();
+        let square_mean = (self.wins as f64 + 0.25 * self.draws as f64) / games;
+        let variance = (square_mean - mean * mean).max(0.0);
+        CONFIDENCE_Z * (variance / games).sqrt()
+    }
+    fn describe(&self) -> String {
+        format!(
+            "{:.1}% +/- {:.1}% (w{} l{} d{})",
+            100.0 * self.win_rate(),
+            100.0 * self.confidence(),
+            self.wins,
+            self.losses,
+            self.draws
+        )
+    }
+}
+
+struct Tuner {
+    referee: String,
+    bot: String,
+    baseline: String,
+    jobs: u64,
+    next_seed: u64,
+    config_counter: u64,
+}
+
+impl Tuner {
+    fn dump_params(&self) -> String {
+        let mut parts = self.bot.split_whitespace();
+        let output = Command::new(parts.next().expect("empty --bot"))
+            .args(parts)
+            .arg("--dump-params")
+            .output()
+            .unwrap_or_else(|err| panic!("couldn't run {}: {}", self.bot, err));
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+    fn write_config(&mut self, params: &[(String, String)]) -> String {
+        self.config_counter += 1;
+        let path = std::env::temp_dir().join(format!(
+            "cb_tuner_{}_{}.cfg",
+            std::process::id(),
+            self.config_counter
+        ));
+        std::fs::write(&path, to_config(params)).expect("couldn't write a temp config");
+        path.to_string_lossy().to_string()
+    }
+    fn play(&mut self, params: &[(String, String)], games: u64, seed: u64) -> Score {
+        // split the games over a few referees running side by side
+        let config = self.write_config(params);
+        let bot = format!("{} --config {}", self.bot, config);
+        let per_job = games.div_ceil(self.jobs);
+        let mut children = Vec::new();
+        let mut started = 0;
+        while started < games {
+            let job_games = per_job.min(games - started);
+            let mut parts = self.referee.split_whitespace();
+            let child = Command::new(parts.next().expect("empty --referee"))
+                .args(parts)
+                .arg("--seed")
+                .arg((seed + started).to_string())
+                .arg("--games")
+                .arg(job_games.to_string())
+                .arg(&bot)
+                .arg(&self.baseline)
+                .stdout(std::process::Stdio::piped())
+                .spawn()
+                .unwrap_or_else(|err| panic!("couldn't run {}: {}", self.referee, err));
+            children.push(child);
+            started += job_games;
+        }
+        let mut score = Score::default();
+        for child in children {
+            let output = child.wait_with_output().expect("referee died");
+            let text = String::from_utf8_lossy(&output.stdout);
+            if let Some(summary) = text.lines().find(|line| line.starts_with("SUMMARY")) {
+                for field in summary.split_whitespace().skip(1) {
+                    let (key, value) = field.split_once('=').unwrap_or(("", ""));
+                    let value = value.parse::<u64>().unwrap_or(0);
+                    match key {
+                        "wins" => score.wins += value,
+                        "losses" => score.losses += value,
+                        "draws" => score.draws += value,
+                        _ => {}
+                    }
+                }
+            } else {
+                eprintln!("referee gave no SUMMARY, counting nothing for that batch");
+            }
+        }
+        let _ = std::fs::remove_file(&config);
+        score
+    }
+    fn fresh_seed(&mut self, games: u64) -> u64 {
+        let seed = self.next_seed;
+        self.next_seed += games;
+        seed
+    }
+}
+
+fn parse_config(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.split('#').next().unwrap().trim();
+            line.split_once('=')
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+// --dump-params tags every line with the param's rust type, "name = value # type"
+fn parse_types(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let (setting, kind) = line.split_once('#')?;
+            let (key, _) = setting.split_once('=')?;
+            Some((key.trim().to_string(), kind.trim().to_string()))
+        })
+        .collect()
+}
+
+fn to_config(params: &[(String, String)]) -> String {
+    params
+        .iter()
+        .map(|(key, value)| format!("{} = {}\n", key, value))
+        .collect()
+}
+
+fn with_point(base: &[(String, String)], tuned: &[Tuned], point: &[f64]) -> Vec<(String, String)> {
+    let mut params = base.to_vec();
+    for (param, unit) in tuned.iter().zip(point.iter()) {
+        let value = param.value_at(*unit);
+        match params.iter_mut().find(|(key, _)| *key == param.name) {
+            Some(entry) => entry.1 = value,
+            None => params.push((param.name.clone(), value)),
+        }
+    }
+    params
+}
+
+fn main() {
+    let mut referee = String::from("./cb_referee");
+    let mut bot = String::from("./cb_bot");
+    let mut baseline = None;
+    let mut params_path = None;
+    let mut tuned = Vec::new();
+    let mut iterations = 50;
+    let mut games = 20;
+    let mut eval_every = 10;
+    let mut eval_games = 100;
+    let mut jobs = 4;
+    let mut seed = 1;
+    let mut out = String::from("best_params.cfg");
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut text = |name: &str| {
+            args.next()
+                .unwrap_or_else(|| panic!("{} needs a value", name))
+        };
+        let number = |name: &str, value: String| {
+            value
+                .parse::<u64>()
+                .unwrap_or_else(|_| panic!("{} needs a number", name))
+        };
+        match arg.as_str() {
+            "--referee" => referee = text("--referee"),
+            "--bot" => bot = text("--bot"),
+            "--baseline" => baseline = Some(text("--baseline")),
+            "--params" => params_path = Some(text("--params")),
+            "--tune" => tuned.push(Tuned::parse(&text("--tune"))),
+            "--iterations" => iterations = number("--iterations", text("--iterations")),
+            "--games" => games = number("--games", text("--games")),
+            "--eval-every" => eval_every = number("--eval-every", text("--eval-every")).max(1),
+            "--eval-games" => eval_games = number("--eval-games", text("--eval-games")),
+            "--jobs" => jobs = number("--jobs", text("--jobs")).max(1),
+            "--seed" => seed = number("--seed", text("--seed")),
+            "--out" => out = text("--out"),
+            _ => panic!("unknown option {}", arg),
+        }
+    }
+    if tuned.is_empty() {
+        eprintln!("usage: cb_tuner [options] --tune NAME:MIN:MAX [--tune ...]");
+        std::process::exit(2);
+    }
+
+    let mut tuner = Tuner {
+        baseline: baseline.unwrap_or_else(|| bot.clone()),
+        referee,
+        bot,
+        jobs,
+        next_seed: seed,
+        config_counter: 0,
+    };
+    let dump = tuner.dump_params();
+    let types = parse_types(&dump);
+    let base = match &params_path {
+        Some(path) => parse_config(
+            &std::fs::read_to_string(path)
+                .unwrap_or_else(|err| panic!("couldn't read {}: {}", path, err)),
+        ),
+        None => parse_config(&dump),
+    };
+    // a bool or anything else that isn't a number can't be walked along a range
+    tuned.retain_mut(|param| {
+        let kind = types
+            .iter()
+            .find(|(key, _)| *key == param.name)
+            .map(|(_, kind)| kind.as_str());
+        match kind {
+            Some("f32" | "f64") => param.is_float = true,
+            Some(
+                "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "usize" | "isize",
+            ) => param.is_float = false,
+            _ => {
+                eprintln!(
+                    "skipping {}, it isn't a numeric param ({:?})",
+                    param.name, kind
+                );
+                return false;
+            }
+        }
+        true
+    });
+    if tuned.is_empty() {
+        eprintln!("nothing numeric left to tune");
+        std::process::exit(2);
+    }
+    let mut point = Vec::new();
+    for param in tuned.iter() {
+        let start = base
+            .iter()
+            .find(|(key, _)| *key == param.name)
+            .unwrap_or_else(|| panic!("the bot has no param called {}", param.name));
+        point.push(param.to_unit(start.1.parse::<f64>().unwrap_or(param.min)));
+    }
+
+    let mut rng = Rng::new(seed);
+    let start_seed = tuner.fresh_seed(eval_games);
+    let mut best_score = tuner.play(&with_point(&base, &tuned, &point), eval_games, start_seed);
+    let mut best_point = point.clone();
+    println!("start: {}", best_score.describe());
+    std::fs::write(&out, to_config(&with_point(&base, &tuned, &best_point)))
+        .unwrap_or_else(|err| panic!("couldn't write {}: {}", out, err));
+
+    for iteration in 0..iterations {
+        // SPSA: nudge every param at once both ways, step along whichever side won more
+        let step = SPSA_STEP / (iteration as f64 + 1.0 + SPSA_STABILITY).powf(SPSA_STEP_DECAY);
+        let perturbation =
+            SPSA_PERTURBATION / (iteration as f64 + 1.0).powf(SPSA_PERTURBATION_DECAY);
+        let delta: Vec<f64> = tuned.iter().map(|_| rng.sign()).collect();
+        let plus: Vec<f64> = point
+            .iter()
+            .zip(delta.iter())
+            .map(|(unit, sign)| (unit + perturbation * sign).clamp(0.0, 1.0))
+            .collect();
+        let minus: Vec<f64> = point
+            .iter()
+            .zip(delta.iter())
+            .map(|(unit, sign)| (unit - perturbation * sign).clamp(0.0, 1.0))
+            .collect();
+        // same maps for both sides so the difference is the params, not the luck
+        let round_seed = tuner.fresh_seed(games);
+        let plus_score = tuner.play(&with_point(&base, &tuned, &plus), games, round_seed);
+        let minus_score = tuner.play(&with_point(&base, &tuned, &minus), games, round_seed);
+        let gain = (plus_score.win_rate() - minus_score.win_rate()) / (2.0 * perturbation);
+        for (unit, sign) in point.iter_mut().zip(delta.iter()) {
+            *unit = (*unit + step * gain * sign).clamp(0.0, 1.0);
+        }
+        println!(
+            "step {}: plus {} minus {} -> {}",
+            iteration + 1,
+            plus_score.describe(),
+            minus_score.describe(),
+            tuned
+                .iter()
+                .zip(point.iter())
+                .map(|(param, unit)| format!("{}={}", param.name, param.value_at(*unit)))
+                .collect::<Vec<String>>()
+                .join(" ")
+        );
+
+        if (iteration + 1) % eval_every == 0 || iteration + 1 == iterations {
+            let eval_seed = tuner.fresh_seed(eval_games);
+            let score = tuner.play(&with_point(&base, &tuned, &point), eval_games, eval_seed);
+            // replay the incumbent on the same maps, scores from different maps don't compare
+            let incumbent = tuner.play(
+                &with_point(&base, &tuned, &best_point),
+                eval_games,
+                eval_seed,
+            );
+            println!(
+                "check: {} vs best {}",
+                score.describe(),
+                incumbent.describe()
+            );
+            if score.win_rate() <= incumbent.win_rate() {
+                best_score = incumbent;
+            } else {
+                best_score = score;
+                best_point = point.clone();
+                std::fs::write(&out, to_config(&with_point(&base, &tuned, &best_point)))
+                    .unwrap_or_else(|err| panic!("couldn't write {}: {}", out, err));
+                println!("new best written to {}", out);
+            }
+        }
+    }
+
+    println!("best: {}", best_score.describe());
+    for (param, unit) in tuned.iter().zip(best_point.iter()) {
+        println!("    {} = {}", param.name, param.value_at(*unit));
+    }
+}